@@ -1,6 +1,11 @@
 //use turn_order::turn_order::{self, TurnOrder, creature::status_effect};
-use turn_order::turn_order::{TurnOrder, creature::status_effect};
-use std::{io, str::FromStr};
+use turn_order::autosave::Autosave;
+use turn_order::compendium::{self, Compendium};
+use turn_order::encounter::{EncounterCreature, EncounterLibrary, PreparedEncounter};
+use turn_order::roster::{CreatureTemplate, Roster};
+use turn_order::session::Session;
+use turn_order::turn_order::{TurnOrder, convert::Format, creature::{damage_modifiers::{DamageModifier, DamageModifiers}, kind::CreatureKind, recurring_effect::RecurringEffect, status_effect}, dice::DiceExpression, entry::Target, initiative};
+use std::{fs, io, path::Path, str::FromStr};
 
 enum CommandResult {
     End,
@@ -13,27 +18,83 @@ enum UserInputResult<T, E> {
     Cancel
 }
 
+// Parses "3" for an entry or "3.2" for the second member of group 3, both 1 based
+struct TargetInput(Target);
+
+impl FromStr for TargetInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_num = |num: &str| match num.trim().parse::<usize>() {
+            Ok(0) => Err(String::from("Numbers start at 1.")),
+            Ok(num) => Ok(num - 1),
+            Err(e) => Err(e.to_string())
+        };
+
+        match s.split_once('.') {
+            Some((entry, member)) => Ok(TargetInput(Target::member(parse_num(entry)?, parse_num(member)?))),
+            None => Ok(TargetInput(Target::entry(parse_num(s)?)))
+        }
+    }
+}
+
 struct TextBased {
-    turn_order: TurnOrder
+    // Every encounter running at once, commands go to the active one
+    session: Session,
+    // DM mode shows hidden creatures and effects, player mode leaves them out
+    dm_mode: bool,
+    // None when there's no data directory to autosave to
+    autosave: Option<Autosave>,
+    roster: Roster,
+    encounters: EncounterLibrary,
+    compendium: Compendium
 }
 
 impl TextBased {
     pub fn start(&mut self) -> Result<(), String> {
-        println!(r#"Available commands: ["Add", "Remove", "Bulk", "(N)ext, Status, Exit]"#);
+        println!(r#"Available commands: ["Add", "Remove", "Bulk", "(N)ext, Status, Move, Hide, Reveal, DM, Surprise, Start, Lair, Event, Legendary, LA, Group, Expand, HP, Damage, Heal, Reaction, Bonus, Speed, Movement, DeathSaves, Save, AutoDeathSaves, Kind, EffectAll, Condition, Adjust, Ongoing, End, Roster Add, Roster List, Roster Load, Order Export, Order Import, Export, Switch, Transfer, Session Save, Session Load, Encounter New, Encounter List, Encounter Load, Import, Defenses, System, Handoff, Modifier, Draws, Redraw, Exit]"#);
+        println!(r#"Add "Goblin x4" to add a group, monsters in the compendium have their stats filled in."#);
+        println!(r#"Group members can be selected as "group.member", e.g. 3.2 for the second member of group 3."#);
         println!(r#"Commands are not case sensitive, if a command has a letter in paretheses, that is its abbreviation."#);
         println!(r#"Additionally, press enter again after (or during in most cases) commands to cancel."#);
+        println!(r#"Export md|html <file> writes the turn order for notes, hidden creatures are only included in DM mode."#);
+        println!(r#"Switch <name> moves to another encounter, starting it if it's new. Transfer moves a creature to another encounter."#);
+        #[cfg(feature = "scripting")]
+        println!(r#"Script loads a Rhai script, or a folder of them, to run alongside the tracker."#);
+
+        self.offer_resume();
     
         loop {
-            let turn_order = &self.turn_order;
-            println!("\n{turn_order}");
+            self.pick_up_changes();
+            if self.session.names().count() > 1 {
+                println!("\n[ENCOUNTER: {}]", self.session.active_name());
+            }
+            if self.dm_mode {
+                let turn_order = self.session.current();
+                println!("\n[DM MODE]\n{turn_order}");
+            }
+            else {
+                let turn_order = self.session.current().player_display();
+                println!("\n{turn_order}");
+            }
             println!("Enter a command.");
 
             let mut user_input = String::new();
             io::stdin().read_line(&mut user_input).expect("failed to read");
-            let command_result = self.process_command(&user_input.trim());
+            self.pick_up_changes();
+            let command_result = self.process_command(user_input.trim());
+            #[cfg(feature = "scripting")]
+            for message in self.session.current_mut().take_script_messages() {
+                println!("{message}");
+            }
             match command_result {
-                CommandResult::Continue => (),
-                CommandResult::End => break
+                CommandResult::Continue => self.autosave(),
+                CommandResult::End => {
+                    if let Some(Err(e)) = self.autosave.as_mut().map(Autosave::clear) {
+                        eprintln!("Error clearing autosave: {e}");
+                    }
+                    break;
+                }
             }
         }
     
@@ -43,6 +104,18 @@ impl TextBased {
     }
     
     fn process_command(&mut self, user_input: &str) -> CommandResult {
+        // Export and switch take their arguments on the same line so names keep their case
+        if let Some((command, args)) = user_input.split_once(' ') {
+            if command.eq_ignore_ascii_case("export") {
+                self.export_rendered(args);
+                return CommandResult::Continue;
+            }
+            if command.eq_ignore_ascii_case("switch") {
+                self.switch_encounter(args);
+                return CommandResult::Continue;
+            }
+        }
+
         match user_input.to_lowercase().as_str() {
             "add" => { self.add_creature(); },
             "remove" => self.remove_creature(),
@@ -50,6 +123,81 @@ impl TextBased {
             "n" => self.next_turn(),
             "next" => self.next_turn(),
             "status" => self.add_status_effect(),
+            "move" => self.move_creature(),
+            "hide" => self.hide_creature(),
+            "surprise" => self.toggle_surprised(),
+            "start" => self.start_combat(),
+            "end" => self.end_combat(),
+            "roster add" => self.add_to_roster(),
+            "roster list" => {
+                for template in self.roster.templates() {
+                    println!("{template}");
+                }
+            }
+            "roster load" => self.load_roster(),
+            "encounter new" => self.prepare_encounter(),
+            "encounter list" => {
+                for encounter in self.encounters.encounters() {
+                    println!("{encounter}");
+                }
+            }
+            "encounter load" => self.load_encounter(),
+            "import" => self.import_monsters(),
+            "order export" => self.export_turn_order(),
+            "order import" => self.import_turn_order(),
+            "export" => self.export_rendered(""),
+            "switch" => self.switch_encounter(""),
+            "transfer" => self.transfer_creature(),
+            "session save" => self.save_session(),
+            "session load" => self.load_session(),
+            #[cfg(feature = "scripting")]
+            "script" => self.import_scripts(),
+            "defenses" => self.set_defenses(),
+            "system" => self.set_initiative_system(),
+            "handoff" => self.hand_off(),
+            "modifier" => self.set_initiative_modifier(),
+            "draws" => self.set_extra_initiative_draws(),
+            "redraw" => {
+                let Some(creature_num) = self.get_creature_num_from_user() else {
+                    return CommandResult::Continue;
+                };
+                match self.session.current_mut().redraw_initiative(creature_num) {
+                    Ok(update) => println!("{update}"),
+                    Err(e) => eprintln!("Error redrawing: {e}")
+                }
+            }
+            "lair" => self.session.current_mut().add_lair_action(),
+            "event" => self.add_event(),
+            "legendary" => self.set_legendary_actions(),
+            "la" => self.spend_legendary_action(),
+            "group" => self.add_group(),
+            "expand" => self.toggle_group_expanded(),
+            "hp" => self.set_max_hit_points(),
+            "damage" => self.change_hit_points(true),
+            "heal" => self.change_hit_points(false),
+            "reaction" => self.spend_action(false),
+            "bonus" => self.spend_action(true),
+            "speed" => self.set_speed(),
+            "movement" => self.spend_movement(),
+            "kind" => self.set_kind(),
+            "effectall" => self.add_status_effect_to_kind(),
+            "condition" => self.add_valued_condition(),
+            "adjust" => self.adjust_condition_value(),
+            "ongoing" => self.add_recurring_effect(),
+            "deathsaves" => self.toggle_death_saves(),
+            "save" => self.record_death_save(),
+            "autodeathsaves" => {
+                let auto_death_saves = !self.session.current().auto_death_saves();
+                self.session.current_mut().set_auto_death_saves(auto_death_saves);
+                let mode = if auto_death_saves { "rolled automatically" } else { "entered manually" };
+                println!("Death saves will be {mode}.");
+            }
+            "reveal" => self.reveal_creature(),
+            "dm" => {
+                self.dm_mode = !self.dm_mode;
+                let mode = if self.dm_mode { "DM" } else { "player" };
+                println!("Switched to {mode} mode.");
+            }
             "exit" => { return CommandResult::End },
             _ => ()
         }
@@ -59,94 +207,851 @@ impl TextBased {
 
     fn add_creature(&mut self) -> CommandResult {
         println!("Please enter the name of the creature.");
-        let name: String;
-        match get_input_from_user::<String>() {
-            UserInputResult::Ok(input_name) => name = input_name,
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
             UserInputResult::Cancel => return CommandResult::End,
             UserInputResult::Err(e) => {
-                eprintln!("Error in name input: {e}");
-                return CommandResult::End;
+                eprintln!("Error in name input: {e}");
+                return CommandResult::End;
+            }
+        };
+
+        let (monster_name, count) = compendium::parse_name_and_count(&name);
+        if let Some(monster) = self.compendium.get(monster_name) {
+            let template = monster.to_template();
+            println!("Enter the initiative, or leave blank to roll ({:+}).", template.initiative_modifier());
+            let Ok(initiative) = get_optional_input_from_user::<isize>() else {
+                return CommandResult::Continue;
+            };
+
+            let initiative = initiative.unwrap_or_else(|| template.roll_initiative());
+            self.session.current_mut().add_group_from_template(&template, initiative, count);
+            return CommandResult::Continue;
+        }
+
+        println!("Enter the initiative.");
+        let initiative = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_init) => input_init,
+            UserInputResult::Cancel => return CommandResult::End,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in initiative input: {e}");
+                return CommandResult::End;
+            }
+
+        };
+
+        match count {
+            1 => self.session.current_mut().add_creature(name, initiative),
+            count => self.session.current_mut().add_group(monster_name.to_string(), initiative, count)
+        }
+        CommandResult::Continue
+    }
+
+    fn import_monsters(&mut self) {
+        println!("Enter the path of a monster JSON file or a folder of them.");
+        let path = match get_input_from_user::<String>() {
+            UserInputResult::Ok(path) => path,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in path input: {e}");
+                return;
+            }
+        };
+
+        match self.compendium.import(Path::new(&path)) {
+            Ok(report) => {
+                println!("Imported {} monster(s).", report.count());
+                for skipped in report.skipped() {
+                    eprintln!("Skipped: {skipped}");
+                }
+            }
+            Err(e) => eprintln!("Error importing monsters: {e}")
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn import_scripts(&mut self) {
+        println!("Enter the path of a Rhai script or a folder of them.");
+        let path = match get_input_from_user::<String>() {
+            UserInputResult::Ok(path) => path,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in path input: {e}");
+                return;
+            }
+        };
+
+        match self.session.current_mut().import_scripts(Path::new(&path)) {
+            Ok(count) => println!("Loaded {count} script(s)."),
+            Err(e) => eprintln!("Error loading scripts: {e}")
+        }
+    }
+
+    // A session autosaved before it crashed or was killed can be picked back up
+    fn offer_resume(&mut self) {
+        let unfinished = match self.autosave.as_ref().map(Autosave::unfinished) {
+            Some(Ok(Some(session))) => session,
+            Some(Ok(None)) | None => return,
+            Some(Err(e)) => {
+                eprintln!("Error reading autosave: {e}");
+                return;
+            }
+        };
+
+        println!("\nThe last session didn't close normally. It had:");
+        for (name, turn_order) in unfinished.names().zip(unfinished.turn_orders()) {
+            println!("\n[ENCOUNTER: {name}]\n{turn_order}");
+        }
+        println!("Resume it? (y/n)");
+        match get_input_from_user::<String>() {
+            UserInputResult::Ok(answer) if answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes") => {
+                self.replace_session(unfinished);
+                println!("Resumed the last session.");
+            }
+            _ => println!("Starting a new turn order.")
+        }
+    }
+
+    fn autosave(&mut self) {
+        if let Some(Err(e)) = self.autosave.as_mut().map(|autosave| autosave.save(&self.session)) {
+            eprintln!("Error autosaving: {e}");
+        }
+    }
+
+    // The web server writes changes made through its API to the autosave
+    fn pick_up_changes(&mut self) {
+        match self.autosave.as_mut().map(Autosave::changed_elsewhere) {
+            Some(Ok(Some(changed))) => {
+                self.replace_session(changed);
+                println!("Picked up changes made from the web server.");
+            }
+            Some(Ok(None)) | None => (),
+            Some(Err(e)) => eprintln!("Error reading autosave: {e}")
+        }
+    }
+
+    // Loaded scripts carry over to the new order
+    fn replace_turn_order(&mut self, turn_order: TurnOrder) {
+        #[cfg(feature = "scripting")]
+        let scripts = std::mem::take(self.session.current_mut().scripts_mut());
+        *self.session.current_mut() = turn_order;
+        #[cfg(feature = "scripting")]
+        {
+            *self.session.current_mut().scripts_mut() = scripts;
+        }
+    }
+
+    fn replace_session(&mut self, session: Session) {
+        #[cfg(feature = "scripting")]
+        let scripts = std::mem::take(self.session.current_mut().scripts_mut());
+        self.session = session;
+        #[cfg(feature = "scripting")]
+        {
+            *self.session.current_mut().scripts_mut() = scripts;
+        }
+    }
+
+    // Without a name, lists the session's encounters
+    fn switch_encounter(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            for (index, encounter_name) in self.session.names().enumerate() {
+                let active = if index == self.session.active() { " [ACTIVE]" } else { "" };
+                println!("{encounter_name}{active}");
+            }
+            return;
+        }
+
+        if self.session.position(name).is_none() {
+            match self.session.add(name.to_string()) {
+                Ok(_) => println!("Started a new encounter, {name}."),
+                Err(e) => {
+                    eprintln!("Error starting encounter: {e}");
+                    return;
+                }
+            }
+        }
+
+        if let Err(e) = self.session.switch(name) {
+            eprintln!("Error switching encounter: {e}");
+        }
+    }
+
+    fn transfer_creature(&mut self) {
+        println!("Enter the number of the creature to move, or group.member for one group member.");
+        let target = match get_input_from_user::<TargetInput>() {
+            UserInputResult::Ok(TargetInput(target)) => target,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in creature num input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the name of the encounter to move it to.");
+        let to = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in encounter input: {e}");
+                return;
+            }
+        };
+
+        match self.session.move_creature(target, &to) {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error moving creature: {e}")
+        }
+    }
+
+    // A blank path uses the session file in the data directory
+    fn save_session(&self) {
+        println!("Enter the path of the file, or leave blank to use the default session file.");
+        let Ok(path) = get_optional_input_from_user::<String>() else {
+            return;
+        };
+
+        let result = match &path {
+            Some(path) => self.session.save_to(Path::new(path)),
+            None => self.session.save()
+        };
+        match result {
+            Ok(_) => println!("Saved the session."),
+            Err(e) => eprintln!("Error saving session: {e}")
+        }
+    }
+
+    fn load_session(&mut self) {
+        println!("Enter the path of the file, or leave blank to use the default session file.");
+        let Ok(path) = get_optional_input_from_user::<String>() else {
+            return;
+        };
+
+        let result = match &path {
+            Some(path) => Session::load_from(Path::new(path)),
+            None => Session::load()
+        };
+        match result {
+            Ok(session) => {
+                self.replace_session(session);
+                println!("Loaded the session.");
+            }
+            Err(e) => eprintln!("Error loading session: {e}")
+        }
+    }
+
+    fn export_turn_order(&mut self) {
+        let Some((path, format)) = get_path_and_format_from_user() else {
+            return;
+        };
+
+        match self.session.current().export(Path::new(&path), format) {
+            Ok(unsupported) => {
+                println!("Saved the turn order as {format} to {path}.");
+                for field in &unsupported {
+                    println!("Not saved: {field}");
+                }
+            }
+            Err(e) => eprintln!("Error saving turn order: {e}")
+        }
+    }
+
+    fn import_turn_order(&mut self) {
+        let Some((path, format)) = get_path_and_format_from_user() else {
+            return;
+        };
+
+        match TurnOrder::import(Path::new(&path), format) {
+            Ok(converted) => {
+                for field in converted.unsupported() {
+                    println!("Not loaded: {field}");
+                }
+                self.replace_turn_order(converted.into_value());
+            }
+            Err(e) => eprintln!("Error loading turn order: {e}")
+        }
+    }
+
+    fn export_rendered(&self, args: &str) {
+        let Some((format, path)) = args.trim().split_once(char::is_whitespace) else {
+            eprintln!("Usage: export md|html <file>");
+            return;
+        };
+
+        let rendered = match format.to_lowercase().as_str() {
+            "md" | "markdown" => self.session.current().to_markdown(self.dm_mode),
+            "html" => self.session.current().to_html(self.dm_mode),
+            _ => {
+                eprintln!("Unknown export format \"{format}\", expected md or html.");
+                return;
+            }
+        };
+
+        let path = path.trim();
+        match fs::write(path, rendered) {
+            Ok(_) if self.dm_mode => println!("Exported the turn order to {path}."),
+            Ok(_) => println!("Exported the player view of the turn order to {path}."),
+            Err(e) => eprintln!("Error writing {path}: {e}")
+        }
+    }
+
+    fn add_to_roster(&mut self) {
+        println!("Please enter the name of the creature.");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the initiative modifier.");
+        let modifier = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in modifier input: {e}");
+                return;
+            }
+        };
+
+        let mut template = CreatureTemplate::new(name, modifier);
+
+        println!("Enter the max HP, or leave blank to not track HP.");
+        let Ok(max_hit_points) = get_optional_input_from_user::<usize>() else {
+            return;
+        };
+        template.set_max_hit_points(max_hit_points);
+
+        println!("Enter passive perception, insight and investigation separated by spaces, or leave blank.");
+        let Ok(passives) = get_optional_input_from_user::<Passives>() else {
+            return;
+        };
+        let Passives(perception, insight, investigation) = passives.unwrap_or_default();
+        template.set_passives(perception, insight, investigation);
+
+        println!("Enter the kind of creature (PC, Ally, Neutral, Enemy or Environment), or leave blank for PC.");
+        let Ok(kind) = get_optional_input_from_user::<CreatureKind>() else {
+            return;
+        };
+        if let Some(kind) = kind {
+            template.set_kind(kind);
+        }
+
+        self.roster.add(template);
+        if let Err(e) = self.roster.save() {
+            eprintln!("Error saving roster: {e}");
+        }
+    }
+
+    // Adds everyone in the roster to the turn order, asking for each initiative roll
+    fn load_roster(&mut self) {
+        let ask_initiative = |template: &CreatureTemplate| {
+            let name = template.name();
+            let modifier = template.initiative_modifier();
+            println!("Enter the initiative for {name} ({modifier:+}), or leave blank to roll.");
+
+            match get_optional_input_from_user::<isize>() {
+                Ok(Some(initiative)) => initiative,
+                _ => {
+                    let initiative = template.roll_initiative();
+                    println!("{name} rolled {initiative}.");
+                    initiative
+                }
+            }
+        };
+
+        if self.session.current().entries().is_empty() {
+            let turn_order = TurnOrder::from_roster(&self.roster, ask_initiative);
+            self.replace_turn_order(turn_order);
+        }
+        else {
+            for template in self.roster.templates() {
+                self.session.current_mut().add_creature_from_template(template, ask_initiative(template));
+            }
+        }
+    }
+
+    fn prepare_encounter(&mut self) {
+        println!("Please enter the name of the encounter.");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter any notes for the encounter, or leave blank.");
+        let Ok(notes) = get_optional_input_from_user::<String>() else {
+            return;
+        };
+
+        let mut encounter = PreparedEncounter::new(name, notes.unwrap_or_default());
+        while let Some(creature) = get_encounter_creature_from_user() {
+            encounter.add_creature(creature);
+        }
+
+        self.encounters.add(encounter);
+        if let Err(e) = self.encounters.save() {
+            eprintln!("Error saving encounter library: {e}");
+        }
+    }
+
+    fn load_encounter(&mut self) {
+        println!("Enter the name of the encounter to load.");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in name input: {e}");
+                return;
+            }
+        };
+
+        let Some(encounter) = self.encounters.get(&name).cloned() else {
+            eprintln!("No encounter named {name}.");
+            return;
+        };
+
+        println!("Add the party roster as well? (y/n)");
+        if let Ok(Some(answer)) = get_optional_input_from_user::<String>() {
+            if answer.eq_ignore_ascii_case("y") {
+                self.load_roster();
+            }
+        }
+
+        self.session.current_mut().load_encounter(&encounter);
+        if !encounter.notes().is_empty() {
+            println!("Notes: {}", encounter.notes());
+        }
+    }
+
+    fn remove_creature(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        if let Err(e) = self.session.current_mut().remove_creature(target) {
+            eprintln!("Error removing creature: {e}");
+            return;
+        }
+        if let Some(reason) = self.session.current().combat_over_reason() {
+            println!("{reason} Enter \"end\" to end combat.");
+        }
+    }
+
+    fn add_group(&mut self) {
+        println!("Please enter the name of the group's creatures.");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the number of creatures in the group.");
+        let count = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_count) => input_count,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in count input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the initiative.");
+        let initiative = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_init) => input_init,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in initiative input: {e}");
+                return;
+            }
+        };
+
+        self.session.current_mut().add_group(name, initiative, count);
+    }
+
+    fn toggle_group_expanded(&mut self) {
+        let Some(group_num) = self.get_creature_num_from_user() else {
+            return;
+        };
+
+        let Some(group) = self.session.current().entries()[group_num].as_group() else {
+            eprintln!("Error: that entry is not a group.");
+            return;
+        };
+
+        let expanded = !group.expanded();
+        if let Err(e) = self.session.current_mut().set_group_expanded(group_num, expanded) {
+            eprintln!("Error expanding group: {e}");
+        }
+    }
+
+    fn set_max_hit_points(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the max HP.");
+        let max = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in HP input: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.session.current_mut().set_max_hit_points(target, max) {
+            eprintln!("Error setting HP: {e}");
+        }
+    }
+
+    fn change_hit_points(&mut self, is_damage: bool) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let kind = if is_damage { "damage" } else { "healing" };
+        println!("Enter the amount of {kind}.");
+        let amount = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in {kind} input: {e}");
+                return;
+            }
+        };
+
+        let result = match is_damage {
+            true => {
+                println!("Enter the damage type, or leave blank for untyped damage.");
+                let Ok(damage_type) = get_optional_input_from_user::<String>() else {
+                    return;
+                };
+
+                println!("Was it a critical hit? (y/n)");
+                let Ok(critical) = get_optional_input_from_user::<String>() else {
+                    return;
+                };
+
+                match (critical.is_some_and(|answer| answer.eq_ignore_ascii_case("y")), damage_type) {
+                    (true, damage_type) => self.session.current_mut().damage_critical(target, amount, damage_type.as_deref()),
+                    (false, Some(damage_type)) => self.session.current_mut().damage_typed(target, amount, &damage_type),
+                    (false, None) => self.session.current_mut().damage(target, amount)
+                }
+            }
+            false => self.session.current_mut().heal(target, amount)
+        };
+
+        match result {
+            Ok(updates) => {
+                for update in &updates {
+                    println!("{update}");
+                }
+            }
+            Err(e) => eprintln!("Error applying {kind}: {e}")
+        }
+    }
+
+    // Replaces the creature's damage modifiers and condition immunities
+    fn set_defenses(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let mut damage_modifiers = DamageModifiers::new();
+        for modifier in [DamageModifier::Resistance, DamageModifier::Immunity, DamageModifier::Vulnerability] {
+            let label = match modifier {
+                DamageModifier::Resistance => "resistances",
+                DamageModifier::Immunity => "immunities",
+                DamageModifier::Vulnerability => "vulnerabilities"
+            };
+            println!("Enter damage {label} separated by commas, or leave blank for none.");
+            let Ok(damage_types) = get_optional_input_from_user::<String>() else {
+                return;
+            };
+            damage_modifiers.set(modifier, split_list(damage_types));
+        }
+
+        println!("Enter condition immunities separated by commas, or leave blank for none.");
+        let Ok(conditions) = get_optional_input_from_user::<String>() else {
+            return;
+        };
+
+        let result = self.session.current_mut().set_damage_modifiers(target, damage_modifiers)
+            .and_then(|_| self.session.current_mut().set_condition_immunities(target, split_list(conditions)));
+        if let Err(e) = result {
+            eprintln!("Error setting defenses: {e}");
+        }
+    }
+
+    fn bulk_add(&mut self) {
+        loop {
+            match self.add_creature() {
+                CommandResult::Continue => (),
+                CommandResult::End => return
+            }
+        }
+    }
+
+    fn set_initiative_system(&mut self) {
+        println!("Enter the initiative system (Standard, Reroll, Side, Popcorn, Cards or Passes).");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in system input: {e}");
+                return;
+            }
+        };
+
+        // Card decks can be seeded so a session's deals can be repeated
+        let system = match name.trim().eq_ignore_ascii_case("cards") {
+            true => {
+                println!("Enter a seed for the shuffle, or leave blank for a random one.");
+                let Ok(seed) = get_optional_input_from_user::<u64>() else {
+                    return;
+                };
+                seed.map(|seed| -> Box<dyn initiative::InitiativeSystem> { Box::new(initiative::cards::CardInitiative::with_seed(seed)) })
+                    .or_else(|| initiative::system_from_name(&name))
+            }
+            false => initiative::system_from_name(&name)
+        };
+
+        match system {
+            Some(system) => {
+                println!("Using {} initiative.", system.name());
+                self.session.current_mut().set_initiative_system(system);
+            }
+            None => eprintln!("Unknown initiative system \"{name}\".")
+        }
+    }
+
+    // The current creature picks who goes next
+    fn hand_off(&mut self) {
+        let Some(creature_num) = self.get_creature_num_from_user() else {
+            return;
+        };
+
+        match self.session.current_mut().hand_off(creature_num) {
+            Ok(updates) => {
+                for update in &updates {
+                    println!("{update}");
+                }
+            }
+            Err(e) => eprintln!("Error handing off: {e}")
+        }
+    }
+
+    fn set_initiative_modifier(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the initiative modifier.");
+        let modifier = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in modifier input: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.session.current_mut().set_initiative_modifier(target, modifier) {
+            eprintln!("Error setting initiative modifier: {e}");
+        }
+    }
+
+    fn set_extra_initiative_draws(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter how many extra cards or d20s to draw for initiative, keeping the best (1 for Level-Headed).");
+        let extra_draws = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in draws input: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.session.current_mut().set_extra_initiative_draws(target, extra_draws) {
+            eprintln!("Error setting initiative draws: {e}");
+        }
+    }
+
+    fn next_turn(&mut self) {
+        match self.session.current_mut().next_turn() {
+            Ok(updates) => {
+                for update in &updates {
+                    println!("{update}");
+                }
+            }
+            Err(e) => eprintln!("Error advancing turn: {e}")
+        }
+    }
+
+    fn spend_action(&mut self, is_bonus_action: bool) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let result = match is_bonus_action {
+            true => self.session.current_mut().spend_bonus_action(target),
+            false => self.session.current_mut().spend_reaction(target)
+        };
+
+        match result {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error: {e}")
+        }
+    }
+
+    fn set_speed(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the speed in feet, or 0 to stop tracking movement.");
+        let speed = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in speed input: {e}");
+                return;
             }
+        };
+
+        if let Err(e) = self.session.current_mut().set_speed(target, speed) {
+            eprintln!("Error setting speed: {e}");
         }
+    }
 
-        println!("Enter the initiative.");
-        let initiative: isize;
-        match get_input_from_user::<isize>() {
-            UserInputResult::Ok(input_init) => initiative = input_init,
-            UserInputResult::Cancel => return CommandResult::End,
+    fn spend_movement(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the distance moved in feet.");
+        let distance = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
             UserInputResult::Err(e) => {
-                eprintln!("Error in initiative input: {e}");
-                return CommandResult::End;
+                eprintln!("Error in distance input: {e}");
+                return;
             }
+        };
 
+        match self.session.current_mut().spend_movement(target, distance) {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error moving: {e}")
         }
+    }
 
-        self.turn_order.add_creature(name, initiative);
-        CommandResult::Continue
+    fn set_kind(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let Some(kind) = get_kind_from_user() else {
+            return;
+        };
+
+        if let Err(e) = self.session.current_mut().set_kind(target, kind) {
+            eprintln!("Error setting creature kind: {e}");
+        }
     }
 
-    fn remove_creature(&mut self) {
-        println!("Enter the number of the creature.");
-        let creature_num: usize;
-        match get_input_from_user::<usize>() {
-            UserInputResult::Ok(input_num) => creature_num = input_num - 1,
+    fn add_status_effect_to_kind(&mut self) {
+        let Some(kind) = get_kind_from_user() else {
+            return;
+        };
+
+        println!("Enter the name of the status effect.");
+        let effect_name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
             UserInputResult::Cancel => return,
             UserInputResult::Err(e) => {
-                eprintln!("Error in creature num input: {e}");
+                eprintln!("Error in effect name input: {e}");
                 return;
             }
-        }
+        };
 
-        self.turn_order.remove_creature(creature_num);
+        let count = self.session.current_mut().add_status_effect_to_kind(kind, effect_name);
+        println!("Applied to {count} creature(s).");
     }
 
-    fn bulk_add(&mut self) {
-        loop {
-            match self.add_creature() {
-                CommandResult::Continue => (),
-                CommandResult::End => return
+    fn toggle_death_saves(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let entry = &self.session.current().entries()[target.index()];
+        let (name, uses_death_saves) = match (target.member_num(), entry.as_group()) {
+            (Some(member_num), Some(group)) => match group.members().get(member_num) {
+                Some(member) => (member.name().to_string(), member.uses_death_saves()),
+                None => (entry.name().to_string(), false)
+            },
+            _ => (entry.name().to_string(), entry.creatures().first().is_some_and(|creature| creature.uses_death_saves()))
+        };
+
+        match self.session.current_mut().set_uses_death_saves(target, !uses_death_saves) {
+            Ok(()) => {
+                let state = if uses_death_saves { "no longer makes" } else { "now makes" };
+                println!("{name} {state} death saving throws.");
             }
+            Err(e) => eprintln!("Error setting death saves: {e}")
         }
     }
 
-    fn next_turn(&mut self) {
-        match self.turn_order.next_turn() {
+    fn record_death_save(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the d20 roll for the death saving throw.");
+        let roll = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in roll input: {e}");
+                return;
+            }
+        };
+
+        match self.session.current_mut().record_death_save(target, roll) {
             Ok(updates) => {
                 for update in &updates {
                     println!("{update}");
                 }
             }
-            Err(e) => eprintln!("Error advancing turn: {e}")
+            Err(e) => eprintln!("Error recording death save: {e}")
         }
     }
 
     fn add_status_effect(&mut self) {
-        println!("Enter the number of the creature.");
-        let creature_num: usize;
-        match get_input_from_user::<usize>() {
-            UserInputResult::Ok(input_num) => {
-                creature_num = input_num - 1;
-                match self.turn_order.creature_num_valid(creature_num) {
-                    Ok(_) => (),
-                    Err(e) => eprintln!("Error: invalid creature index.")
-                }
-            },
-            UserInputResult::Cancel => return,
-            UserInputResult::Err(e) => {
-                eprintln!("Error in creature num input: {e}");
-                return;
-            }
-        }
+        let Some(creature_num) = self.get_target_from_user() else {
+            return;
+        };
         println!("Enter the name of the status effect.");
-        let effect_name: String;
-        match get_input_from_user::<String>() {
-            UserInputResult::Ok(name) => effect_name = name,
+        let effect_name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
             UserInputResult::Cancel => return,
             UserInputResult::Err(e) => {
                 eprintln!("Error in creature num input: {e}");
                 return;
             }
-        }
+        };
 
         /*
             Any cancels will be interpreted as default statuses, but
@@ -177,7 +1082,7 @@ impl TextBased {
                     }
                 }
                 UserInputResult::Cancel => {
-                    self.turn_order.add_status_effect(creature_num, effect_name);
+                    self.add_status_effect_indefinite(creature_num, effect_name);
                     return;
                 }
                 UserInputResult::Err(e) => {
@@ -188,25 +1093,486 @@ impl TextBased {
         }
 
         println!("Enter duration in number of turns, or press enter again for indefinite clear.");
-        let duration: usize;
-        match get_input_from_user::<usize>() {
-            UserInputResult::Ok(user_num) => duration = user_num,
+        let duration = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(user_num) => user_num,
             UserInputResult::Cancel => {
-                self.turn_order.add_status_effect(creature_num, effect_name);
+                self.add_status_effect_indefinite(creature_num, effect_name);
                 return;
             }
             UserInputResult::Err(e) => {
                 eprintln!("Error in duration input: {e}");
                 return;
             }
-        }
+        };
 
-        match self.turn_order.add_status_effect_timed(creature_num, effect_name, duration, clear_type) {
+        match self.session.current_mut().add_status_effect_timed(creature_num, effect_name, duration, clear_type) {
             Ok(_) => (),
             Err(e) => eprintln!("Error adding completed status effect: {e}")
         }
         
     }
+
+    fn add_valued_condition(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the name of the condition.");
+        let effect_name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in condition name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the condition's value.");
+        let value = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in value input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter how the value changes at the end of each turn (\"decrement\", \"persistent\" or \"increment\"), or press enter again to decrement.");
+        let rule = match get_optional_input_from_user::<status_effect::ValueRule>() {
+            Ok(rule) => rule.unwrap_or_default(),
+            Err(()) => return
+        };
+
+        if let Err(e) = self.session.current_mut().add_status_effect_valued(target, effect_name, value, rule) {
+            eprintln!("Error adding condition: {e}");
+        }
+    }
+
+    fn adjust_condition_value(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the status effect number, counting from 1 in the order they are listed.");
+        let effect_num = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in status effect number input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the change in value, e.g. 1 or -2.");
+        let change = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in value input: {e}");
+                return;
+            }
+        };
+
+        match self.session.current_mut().adjust_condition_value(target, effect_num.saturating_sub(1), change) {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error adjusting condition: {e}")
+        }
+    }
+
+    fn add_recurring_effect(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        println!("Enter the name of the effect.");
+        let effect_name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(name) => name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in effect name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the amount rolled each turn, e.g. 2d6 or 10.");
+        let amount = match get_input_from_user::<DiceExpression>() {
+            UserInputResult::Ok(amount) => amount,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in amount input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter when it happens (\"start\" or \"end\" of turn), or press enter again for start.");
+        let trigger = match get_optional_input_from_user::<String>() {
+            Ok(Some(trigger)) if trigger.eq_ignore_ascii_case("end") => status_effect::ClearType::EndOfTurn,
+            Ok(_) => status_effect::ClearType::BeginningOfTurn,
+            Err(()) => return
+        };
+
+        println!("Enter the damage type, \"heal\" for healing, or press enter again for untyped damage.");
+        let mut recurring = match get_optional_input_from_user::<String>() {
+            Ok(Some(damage_type)) if damage_type.eq_ignore_ascii_case("heal") => RecurringEffect::healing(trigger, amount),
+            Ok(damage_type) => RecurringEffect::damage(trigger, amount, damage_type),
+            Err(()) => return
+        };
+
+        println!("Enter the DC of the save that ends it, or press enter again for no save.");
+        let save_dc = match get_optional_input_from_user::<usize>() {
+            Ok(save_dc) => save_dc,
+            Err(()) => return
+        };
+
+        if let Some(dc) = save_dc {
+            println!("Enter the creature's save modifier, or press enter again for +0.");
+            let modifier = match get_optional_input_from_user::<isize>() {
+                Ok(modifier) => modifier.unwrap_or(0),
+                Err(()) => return
+            };
+            recurring = recurring.with_save(dc, modifier);
+        }
+
+        println!("Enter the duration in number of turns, or press enter again for indefinite.");
+        let duration = match get_optional_input_from_user::<usize>() {
+            Ok(duration) => duration,
+            Err(()) => return
+        };
+
+        if let Err(e) = self.session.current_mut().add_status_effect_recurring(target, effect_name, duration, recurring) {
+            eprintln!("Error adding ongoing effect: {e}");
+        }
+    }
+
+    fn add_status_effect_indefinite(&mut self, creature_num: Target, effect_name: String) {
+        if let Err(e) = self.session.current_mut().add_status_effect(creature_num, effect_name) {
+            eprintln!("Error adding status effect: {e}");
+        }
+    }
+
+    fn add_event(&mut self) {
+        println!("Please enter the name of the event.");
+        let name = match get_input_from_user::<String>() {
+            UserInputResult::Ok(input_name) => input_name,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in name input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the initiative it takes place on.");
+        let initiative = match get_input_from_user::<isize>() {
+            UserInputResult::Ok(input_init) => input_init,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in initiative input: {e}");
+                return;
+            }
+        };
+
+        self.session.current_mut().add_event(name, initiative);
+    }
+
+    fn set_legendary_actions(&mut self) {
+        let Some(creature_num) = self.get_creature_num_from_user() else {
+            return;
+        };
+
+        println!("Enter the number of legendary actions per round, or 0 to remove them.");
+        let actions = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => input_num,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in legendary action input: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.session.current_mut().set_legendary_actions(creature_num, actions) {
+            eprintln!("Error setting legendary actions: {e}");
+        }
+    }
+
+    fn spend_legendary_action(&mut self) {
+        let Some(creature_num) = self.get_creature_num_from_user() else {
+            return;
+        };
+
+        match self.session.current_mut().spend_legendary_action(creature_num) {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error using legendary action: {e}")
+        }
+    }
+
+    fn toggle_surprised(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        let entry = &self.session.current().entries()[target.index()];
+        let currently_surprised = match (target.member_num(), entry.as_group()) {
+            (Some(member_num), Some(group)) => group.members().get(member_num).is_some_and(|member| member.surprised()),
+            _ => entry.surprised()
+        };
+
+        if let Err(e) = self.session.current_mut().set_surprised(target, !currently_surprised) {
+            eprintln!("Error marking creature surprised: {e}");
+        }
+    }
+
+    fn start_combat(&mut self) {
+        match self.session.current_mut().start_combat() {
+            Ok(updates) => {
+                for update in &updates {
+                    println!("{update}");
+                }
+            }
+            Err(e) => eprintln!("Error starting combat: {e}")
+        }
+    }
+
+    fn end_combat(&mut self) {
+        let summary = match self.session.current_mut().end_combat() {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Error ending combat: {e}");
+                return;
+            }
+        };
+
+        println!("{}", summary.to_markdown());
+
+        println!("Enter a file to save the summary to (.md or .json), or leave blank to skip.");
+        let path = match get_input_from_user::<String>() {
+            UserInputResult::Ok(path) => path,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in file input: {e}");
+                return;
+            }
+        };
+
+        let contents = match path.ends_with(".json") {
+            true => match summary.to_json() {
+                Ok(json) => json,
+                Err(e) => {
+                    eprintln!("Error exporting summary: {e}");
+                    return;
+                }
+            },
+            false => summary.to_markdown()
+        };
+
+        match fs::write(&path, contents) {
+            Ok(()) => println!("Saved summary to {path}."),
+            Err(e) => eprintln!("Error saving summary: {e}")
+        }
+    }
+
+    fn hide_creature(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        if let Err(e) = self.session.current_mut().set_creature_hidden(target, true) {
+            eprintln!("Error hiding creature: {e}");
+        }
+    }
+
+    fn reveal_creature(&mut self) {
+        let Some(target) = self.get_target_from_user() else {
+            return;
+        };
+
+        match self.session.current_mut().reveal_creature(target) {
+            Ok(update) => println!("{update}"),
+            Err(e) => eprintln!("Error revealing creature: {e}")
+        }
+    }
+
+    /*
+        Prompts for a 1-based creature number and returns it 0-based.
+        None if cancelled or the number isn't a valid creature.
+     */
+    fn get_creature_num_from_user(&self) -> Option<usize> {
+        println!("Enter the number of the creature.");
+        match get_input_from_user::<usize>() {
+            UserInputResult::Ok(input_num) => {
+                let creature_num = input_num.wrapping_sub(1);
+                match self.session.current().creature_num_valid(creature_num) {
+                    Ok(_) => Some(creature_num),
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        None
+                    }
+                }
+            }
+            UserInputResult::Cancel => None,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in creature num input: {e}");
+                None
+            }
+        }
+    }
+
+    /*
+        Like get_creature_num_from_user, but also accepts a group
+        member as "group.member".
+     */
+    fn get_target_from_user(&self) -> Option<Target> {
+        println!("Enter the number of the creature.");
+        match get_input_from_user::<TargetInput>() {
+            UserInputResult::Ok(TargetInput(target)) => {
+                match self.session.current().creature_num_valid(target.index()) {
+                    Ok(_) => Some(target),
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        None
+                    }
+                }
+            }
+            UserInputResult::Cancel => None,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in creature num input: {e}");
+                None
+            }
+        }
+    }
+
+    fn move_creature(&mut self) {
+        println!("Enter the number of the creature to move.");
+        let from = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(0) => {
+                eprintln!("Error in creature num input: Numbers start at 1.");
+                return;
+            }
+            UserInputResult::Ok(input_num) => input_num - 1,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in creature num input: {e}");
+                return;
+            }
+        };
+
+        println!("Enter the position to move it to.");
+        let to = match get_input_from_user::<usize>() {
+            UserInputResult::Ok(0) => {
+                eprintln!("Error in position input: Numbers start at 1.");
+                return;
+            }
+            UserInputResult::Ok(input_num) => input_num - 1,
+            UserInputResult::Cancel => return,
+            UserInputResult::Err(e) => {
+                eprintln!("Error in position input: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.session.current_mut().move_creature(from, to) {
+            eprintln!("Error moving creature: {e}");
+        }
+    }
+}
+
+// The format is detected from the file when left blank
+fn get_path_and_format_from_user() -> Option<(String, Format)> {
+    println!("Enter the path of the file.");
+    let path = match get_input_from_user::<String>() {
+        UserInputResult::Ok(path) => path,
+        UserInputResult::Cancel => return None,
+        UserInputResult::Err(e) => {
+            eprintln!("Error in path input: {e}");
+            return None;
+        }
+    };
+
+    println!("Enter the format (\"json\", \"csv\" or \"ii\" for Improved Initiative), or press enter again to detect it from the file.");
+    let format = get_optional_input_from_user::<Format>().ok()?
+        .unwrap_or_else(|| Format::from_path(Path::new(&path)));
+
+    Some((path, format))
+}
+
+fn get_kind_from_user() -> Option<CreatureKind> {
+    println!("Enter the kind of creature (PC, Ally, Neutral, Enemy or Environment).");
+    match get_input_from_user::<CreatureKind>() {
+        UserInputResult::Ok(kind) => Some(kind),
+        UserInputResult::Cancel => None,
+        UserInputResult::Err(e) => {
+            eprintln!("Error in kind input: {e}");
+            None
+        }
+    }
+}
+
+// Asks for one creature of a prepared encounter, None once the user leaves the name blank
+fn get_encounter_creature_from_user() -> Option<EncounterCreature> {
+    println!("Enter the name of the next creature, or leave blank to finish.");
+    let name = get_optional_input_from_user::<String>().ok()??;
+
+    println!("Enter the initiative modifier.");
+    let modifier = get_optional_input_from_user::<isize>().ok()?.unwrap_or(0);
+
+    println!("Enter how many there are, or leave blank for 1.");
+    let count = get_optional_input_from_user::<usize>().ok()?.unwrap_or(1);
+
+    let mut template = CreatureTemplate::new(name, modifier);
+    template.set_kind(CreatureKind::Enemy);
+
+    println!("Enter the max HP, or leave blank to not track HP.");
+    template.set_max_hit_points(get_optional_input_from_user::<usize>().ok()?);
+
+    let mut creature = EncounterCreature::new(template, count);
+
+    println!("Enter starting effects separated by commas, or leave blank.");
+    creature.set_starting_effects(split_list(get_optional_input_from_user::<String>().ok()?));
+
+    println!("Should it start hidden? (y/n)");
+    let hidden = get_optional_input_from_user::<String>().ok()?;
+    creature.set_hidden(hidden.is_some_and(|answer| answer.eq_ignore_ascii_case("y")));
+
+    Some(creature)
+}
+
+// "fire, cold" becomes ["fire", "cold"], blank input is an empty list
+fn split_list(input: Option<String>) -> Vec<String> {
+    input.unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Up to three optional passive scores, e.g. "14 12 10"
+#[derive(Default)]
+struct Passives(Option<usize>, Option<usize>, Option<usize>);
+
+impl FromStr for Passives {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scores = s.split_whitespace().map(|score| score.parse::<usize>().map_err(|e| e.to_string()));
+        let mut next = || scores.next().transpose();
+
+        Ok(Passives(next()?, next()?, next()?))
+    }
+}
+
+// Blank input is None rather than a cancel, errors are reported here
+fn get_optional_input_from_user<T>() -> Result<Option<T>, ()>
+where
+    T: FromStr,
+    <T as FromStr>::Err: ToString
+{
+    match get_input_from_user::<T>() {
+        UserInputResult::Ok(input) => Ok(Some(input)),
+        UserInputResult::Cancel => Ok(None),
+        UserInputResult::Err(e) => {
+            eprintln!("Error in input: {e}");
+            Err(())
+        }
+    }
 }
 
 /*
@@ -235,7 +1601,39 @@ where
 }
 
 pub fn start() {
-    let mut cli = TextBased { turn_order: TurnOrder::new() };
-    cli.start();
+    let roster = Roster::load().unwrap_or_else(|e| {
+        eprintln!("Error loading roster: {e}");
+        Roster::new()
+    });
+
+    let encounters = EncounterLibrary::load().unwrap_or_else(|e| {
+        eprintln!("Error loading encounter library: {e}");
+        EncounterLibrary::new()
+    });
+
+    let compendium = match Compendium::load() {
+        Ok((compendium, report)) => {
+            for skipped in report.skipped() {
+                eprintln!("Skipped: {skipped}");
+            }
+            compendium
+        }
+        Err(e) => {
+            eprintln!("Error loading compendium: {e}");
+            Compendium::new()
+        }
+    };
+
+    let autosave = Autosave::new().map_err(|e| eprintln!("Autosave is off: {e}")).ok();
+
+    let mut cli = TextBased { session: Session::new(), dm_mode: true, autosave, roster, encounters, compendium };
+    #[cfg(feature = "scripting")]
+    if let Err(e) = cli.session.current_mut().scripts_mut().load() {
+        eprintln!("Error loading scripts: {e}");
+    }
+
+    if let Err(e) = cli.start() {
+        eprintln!("{e}");
+    }
 }
 
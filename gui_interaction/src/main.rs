@@ -1,9 +1,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 #![allow(rustdoc::missing_crate_level_docs)] // it's an example
 
+mod player_view;
+
 use eframe::egui;
-use turn_order::turn_order::{TurnOrder, creature::status_effect};
-use std::{io, str};
+use turn_order::autosave::Autosave;
+use turn_order::compendium::{self, Compendium};
+use turn_order::encounter::EncounterLibrary;
+use turn_order::roster::Roster;
+use turn_order::session::Session;
+use turn_order::turn_order::{TurnOrder, convert::Format, creature::{Creature, damage_modifiers::DamageModifier, kind::CreatureKind, recurring_effect::RecurringEffect, status_effect::{ClearType, ValueRule}}, dice::DiceExpression, entry::Target, initiative};
+use std::{path::Path, str, time::Duration};
 
 fn main() -> eframe::Result {
     //env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
@@ -16,8 +23,72 @@ fn main() -> eframe::Result {
     // Our application state:
     let mut name = "Arthur".to_owned();
     let mut initiative_input = "3".to_owned();
+    let mut target_input = "1".to_owned();
+    let mut legendary_input = "3".to_owned();
+    let mut group_size_input = "1".to_owned();
+    let mut amount_input = "0".to_owned();
+    let mut damage_type = String::new();
+    let mut critical_hit = false;
+    let mut effect_name = String::new();
+    let mut effect_hidden = false;
+    let mut effect_kind = CreatureKind::Enemy;
+    let mut condition_value_input = "1".to_owned();
+    let mut value_rule = ValueRule::default();
+    let mut recurring_dice = "2d6".to_owned();
+    let mut recurring_healing = false;
+    let mut recurring_at_end = false;
+    let mut save_dc_input = String::new();
+    let mut save_modifier_input = String::new();
+    let mut recurring_duration_input = String::new();
+    let mut encounter_name_input = String::new();
+    let mut seed_input = String::new();
+    let mut updates: Vec<String> = Vec::new();
+
+    // Each tab is its own turn order, for when the party splits up
+    let mut session = Session::new();
+    // Scripts in the data directory's scripts folder are picked up at launch
+    #[cfg(feature = "scripting")]
+    if let Err(e) = session.current_mut().scripts_mut().load() {
+        updates.push(format!("Error loading scripts: {e}"));
+    }
+    let mut player_view = player_view::PlayerView::default();
+
+    // Prepared encounters and the party roster, rolled for automatically when loaded
+    let mut encounters = EncounterLibrary::load().unwrap_or_else(|e| {
+        updates.push(format!("Error loading encounter library: {e}"));
+        EncounterLibrary::new()
+    });
+    let roster = Roster::load().unwrap_or_else(|e| {
+        updates.push(format!("Error loading roster: {e}"));
+        Roster::new()
+    });
+    let mut include_roster = true;
+    let mut file_path = String::from("turn_order.json");
+    let mut file_format = Format::Native;
+    let compendium = match Compendium::load() {
+        Ok((compendium, report)) => {
+            updates.extend(report.skipped().iter().map(|skipped| format!("Skipped: {skipped}")));
+            compendium
+        }
+        Err(e) => {
+            updates.push(format!("Error loading compendium: {e}"));
+            Compendium::new()
+        }
+    };
 
-    let mut order = TurnOrder::new();
+    // Saved every few seconds, and offered back at launch if the last session didn't close normally
+    let autosave_interval = Duration::from_secs(5);
+    let mut autosave = Autosave::new().map(|autosave| autosave.with_interval(autosave_interval))
+        .map_err(|e| updates.push(format!("Autosave is off: {e}")))
+        .ok();
+    let mut unfinished = match autosave.as_ref().map(Autosave::unfinished) {
+        Some(Ok(turn_order)) => turn_order,
+        Some(Err(e)) => {
+            updates.push(format!("Error reading autosave: {e}"));
+            None
+        }
+        None => None
+    };
 
     /*
     eframe::run_simple_native("My egui App", options, move |ctx, _frame| {
@@ -39,7 +110,81 @@ fn main() -> eframe::Result {
     */
 
     eframe::run_simple_native("Initiative Tracker", options, move |ctx, _frame| {
+        // The web server writes changes made through its API to the autosave
+        if let Some(autosave) = autosave.as_mut().filter(|_| unfinished.is_none()) {
+            match autosave.changed_elsewhere() {
+                Ok(Some(changed)) => replace_session(&mut session, changed),
+                Ok(None) => (),
+                Err(e) => updates = vec![format!("Error reading autosave: {e}")]
+            }
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
+            if unfinished.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("The last session didn't close normally.");
+                    if ui.button("Resume It").clicked() {
+                        if let Some(resumed) = unfinished.take() {
+                            replace_session(&mut session, resumed);
+                            updates = vec![String::from("Resumed the last session.")];
+                        }
+                    }
+                    if ui.button("Discard It").clicked() {
+                        unfinished = None;
+                    }
+                });
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                let mut switch_to = None;
+                for (index, encounter_name) in session.names().enumerate() {
+                    if ui.selectable_label(index == session.active(), encounter_name).clicked() {
+                        switch_to = Some(index);
+                    }
+                }
+                if let Some(Err(e)) = switch_to.map(|index| session.switch_to(index)) {
+                    updates = vec![format!("Error switching encounter: {e}")];
+                }
+
+                ui.separator();
+                ui.add(egui::TextEdit::singleline(&mut encounter_name_input).desired_width(120.0).hint_text("Encounter name"));
+                if ui.button("New Encounter").clicked() {
+                    match session.add(encounter_name_input.clone()) {
+                        Ok(index) => {
+                            let _ = session.switch_to(index);
+                            encounter_name_input.clear();
+                        }
+                        Err(e) => updates = vec![format!("Error starting encounter: {e}")]
+                    }
+                }
+                if ui.add_enabled(session.names().count() > 1, egui::Button::new("Close Encounter")).clicked() {
+                    let active_name = session.active_name().to_string();
+                    if let Err(e) = session.remove(&active_name) {
+                        updates = vec![format!("Error closing encounter: {e}")];
+                    }
+                }
+            });
+            // The creature in the target field is moved, with its HP and effects
+            if session.names().count() > 1 {
+                ui.horizontal(|ui| {
+                    let other_names: Vec<String> = session.names().enumerate()
+                        .filter(|(index, _)| *index != session.active())
+                        .map(|(_, encounter_name)| encounter_name.to_string())
+                        .collect();
+                    for encounter_name in other_names {
+                        if ui.button(format!("Move Target to {encounter_name}")).clicked() {
+                            updates = match parse_target(&target_input) {
+                                Some(target) => vec![session.move_creature(target, &encounter_name).unwrap_or_else(|e| format!("Error moving creature: {e}"))],
+                                None => vec![String::from("Invalid creature number.")]
+                            };
+                        }
+                    }
+                });
+            }
+            ui.separator();
+
+            let order = session.current_mut();
             ui.horizontal(|ui| {
                 let name_label = ui.label("Creature Name:");
                 ui.text_edit_singleline(&mut name)
@@ -50,23 +195,503 @@ fn main() -> eframe::Result {
                 ui.text_edit_singleline(&mut initiative_input)
                     .labelled_by(name_label.id);
             });
-            if ui.button("Add Creature").clicked() {
-                let initiative: isize;
+            ui.horizontal(|ui| {
+                let size_label = ui.label("Group Size:");
+                ui.add(egui::TextEdit::singleline(&mut group_size_input).desired_width(40.0))
+                    .labelled_by(size_label.id);
+                if ui.button("Add Creature").clicked() {
+                    match try_get_type::<isize>(&initiative_input) {
+                        UserInputResult::Ok(num) => order.add_creature(name.to_string(), num),
+                        UserInputResult::Err(_) => (),
+                        UserInputResult::None => ()
 
-                match try_get_type::<isize>(&initiative_input) {
-                    UserInputResult::Ok(num) => order.add_creature(name.to_string(), num),
-                    UserInputResult::Err(e) => (),
-                    UserInputResult::None => ()
+                    }
+                }
+                if ui.button("Add Group").clicked() {
+                    match (try_get_type::<isize>(&initiative_input), try_get_type::<usize>(&group_size_input)) {
+                        (UserInputResult::Ok(num), UserInputResult::Ok(count)) if count > 0 => {
+                            order.add_group(name.to_string(), num, count);
+                        }
+                        _ => updates = vec![String::from("Invalid initiative or group size.")]
+                    }
+                }
+                // Takes names like "Goblin x4" and rolls initiative from the monster's stats
+                if ui.button("Add from Compendium").clicked() {
+                    let (monster_name, count) = compendium::parse_name_and_count(&name);
+                    match compendium.get(monster_name) {
+                        Some(monster) => {
+                            let template = monster.to_template();
+                            order.add_group_from_template(&template, template.roll_initiative(), count);
+                        }
+                        None => updates = vec![format!("No monster named {monster_name} in the compendium.")]
+                    }
+                }
+                if ui.button("Add Lair Action").clicked() {
+                    order.add_lair_action();
+                }
+            });
+            ui.horizontal(|ui| {
+                let creature_label = ui.label("Target Creature # (3 or 3.2):");
+                ui.add(egui::TextEdit::singleline(&mut target_input).desired_width(40.0))
+                    .labelled_by(creature_label.id);
+                let legendary_label = ui.label("Legendary Actions:");
+                ui.add(egui::TextEdit::singleline(&mut legendary_input).desired_width(40.0))
+                    .labelled_by(legendary_label.id);
+                if ui.button("Set Init Modifier").clicked() {
+                    match (parse_target(&target_input), try_get_type::<isize>(&initiative_input)) {
+                        (Some(target), UserInputResult::Ok(modifier)) => {
+                            if let Err(e) = order.set_initiative_modifier(target, modifier) {
+                                updates = vec![format!("Error setting initiative modifier: {e}")];
+                            }
+                        }
+                        _ => updates = vec![String::from("Enter the modifier in the initiative field.")]
+                    }
+                }
+                if ui.button("Set Legendary Actions").clicked() {
+                    match (parse_target(&target_input), try_get_type::<usize>(&legendary_input)) {
+                        (Some(target), UserInputResult::Ok(actions)) => {
+                            if let Err(e) = order.set_legendary_actions(target, actions) {
+                                updates = vec![format!("Error setting legendary actions: {e}")];
+                            }
+                        }
+                        _ => updates = vec![String::from("Invalid creature number or action count.")]
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let effect_label = ui.label("Effect Name:");
+                ui.text_edit_singleline(&mut effect_name)
+                    .labelled_by(effect_label.id);
+                ui.checkbox(&mut effect_hidden, "Hidden");
+                if ui.button("Add Effect").clicked() {
+                    match parse_target(&target_input) {
+                        Some(target) => {
+                            let result = match effect_hidden {
+                                true => order.add_status_effect_hidden(target, effect_name.to_string()),
+                                false => order.add_status_effect(target, effect_name.to_string())
+                            };
+                            if let Err(e) = result {
+                                updates = vec![format!("Error adding status effect: {e}")];
+                            }
+                        }
+                        None => updates = vec![String::from("Invalid creature number.")]
+                    }
+                }
+                kind_combo_box(ui, "effect_kind", &mut effect_kind);
+                if ui.button("Add Effect to All").clicked() {
+                    let count = order.add_status_effect_to_kind(effect_kind, effect_name.to_string());
+                    updates = vec![format!("Applied {effect_name} to {count} creature(s).")];
+                }
+            });
+            ui.horizontal(|ui| {
+                let value_label = ui.label("Condition Value:");
+                ui.add(egui::TextEdit::singleline(&mut condition_value_input).desired_width(40.0))
+                    .labelled_by(value_label.id);
+                egui::ComboBox::from_id_salt("value_rule")
+                    .selected_text(value_rule.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in ValueRule::ALL {
+                            ui.selectable_value(&mut value_rule, option, option.to_string());
+                        }
+                    });
+                if ui.button("Add Valued Condition").clicked() {
+                    match (parse_target(&target_input), try_get_type::<usize>(&condition_value_input)) {
+                        (Some(target), UserInputResult::Ok(value)) => {
+                            if let Err(e) = order.add_status_effect_valued(target, effect_name.to_string(), value, value_rule) {
+                                updates = vec![format!("Error adding condition: {e}")];
+                            }
+                        }
+                        _ => updates = vec![String::from("Invalid creature number or condition value.")]
+                    }
+                }
+            });
+            // Ongoing damage uses the damage type field, left empty for untyped damage
+            ui.horizontal(|ui| {
+                let dice_label = ui.label("Ongoing Dice:");
+                ui.add(egui::TextEdit::singleline(&mut recurring_dice).desired_width(60.0))
+                    .labelled_by(dice_label.id);
+                ui.checkbox(&mut recurring_healing, "Healing");
+                ui.checkbox(&mut recurring_at_end, "End of Turn");
+                let dc_label = ui.label("Save DC:");
+                ui.add(egui::TextEdit::singleline(&mut save_dc_input).desired_width(30.0))
+                    .labelled_by(dc_label.id);
+                let modifier_label = ui.label("Save Mod:");
+                ui.add(egui::TextEdit::singleline(&mut save_modifier_input).desired_width(30.0))
+                    .labelled_by(modifier_label.id);
+                let duration_label = ui.label("Turns:");
+                ui.add(egui::TextEdit::singleline(&mut recurring_duration_input).desired_width(30.0).hint_text("forever"))
+                    .labelled_by(duration_label.id);
+                if ui.button("Add Ongoing Effect").clicked() {
+                    let (Some(target), UserInputResult::Ok(amount)) = (parse_target(&target_input), try_get_type::<DiceExpression>(&recurring_dice)) else {
+                        updates = vec![String::from("Invalid creature number or dice expression.")];
+                        return;
+                    };
+                    // Left blank, the effect lasts until it's saved against or removed
+                    let duration = match try_get_type::<usize>(&recurring_duration_input) {
+                        UserInputResult::Ok(duration) => Some(duration),
+                        UserInputResult::None => None,
+                        UserInputResult::Err(e) => {
+                            updates = vec![format!("Invalid duration: {e}")];
+                            return;
+                        }
+                    };
+
+                    let trigger = if recurring_at_end { ClearType::EndOfTurn } else { ClearType::BeginningOfTurn };
+                    let mut recurring = match recurring_healing {
+                        true => RecurringEffect::healing(trigger, amount),
+                        false => RecurringEffect::damage(trigger, amount, Some(damage_type.trim().to_string()).filter(|damage_type| !damage_type.is_empty()))
+                    };
+                    if let UserInputResult::Ok(dc) = try_get_type::<usize>(&save_dc_input) {
+                        let modifier = match try_get_type::<isize>(&save_modifier_input) {
+                            UserInputResult::Ok(modifier) => modifier,
+                            _ => 0
+                        };
+                        recurring = recurring.with_save(dc, modifier);
+                    }
+
+                    if let Err(e) = order.add_status_effect_recurring(target, effect_name.to_string(), duration, recurring) {
+                        updates = vec![format!("Error adding ongoing effect: {e}")];
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let amount_label = ui.label("Amount:");
+                ui.add(egui::TextEdit::singleline(&mut amount_input).desired_width(40.0))
+                    .labelled_by(amount_label.id);
+                ui.checkbox(&mut critical_hit, "Critical");
+
+                let mut clicked = None;
+                for label in ["Set Max HP", "Damage", "Heal", "Set Speed", "Set Extra Init Draws"] {
+                    if ui.button(label).clicked() {
+                        clicked = Some(label);
+                    }
+                }
+                let Some(clicked) = clicked else {
+                    return;
+                };
+
+                // The fields are only read once a button is clicked, so a bad one can be reported
+                let target = parse_target(&target_input).ok_or_else(|| String::from("Invalid creature number."));
+                let amount = match try_get_type::<usize>(&amount_input) {
+                    UserInputResult::Ok(amount) => Ok(amount),
+                    UserInputResult::Err(e) => Err(format!("Invalid amount: {e}")),
+                    _ => Err(String::from("Enter an amount."))
+                };
+                let (target, amount) = match target.and_then(|target| Ok((target, amount?))) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        updates = vec![e];
+                        return;
+                    }
+                };
+
+                match clicked {
+                    "Set Max HP" => if let Err(e) = order.set_max_hit_points(target, amount) {
+                        updates = vec![format!("Error setting HP: {e}")];
+                    },
+                    "Damage" => {
+                        let result = match (critical_hit, damage_type.trim()) {
+                            (true, "") => order.damage_critical(target, amount, None),
+                            (true, damage_type) => order.damage_critical(target, amount, Some(damage_type)),
+                            (false, "") => order.damage(target, amount),
+                            (false, damage_type) => order.damage_typed(target, amount, damage_type)
+                        };
+                        updates = result.unwrap_or_else(|e| vec![format!("Error applying damage: {e}")]);
+                    }
+                    "Heal" => updates = order.heal(target, amount).unwrap_or_else(|e| vec![format!("Error applying healing: {e}")]),
+                    "Set Speed" => if let Err(e) = order.set_speed(target, amount) {
+                        updates = vec![format!("Error setting speed: {e}")];
+                    },
+                    _ => if let Err(e) = order.set_extra_initiative_draws(target, amount) {
+                        updates = vec![format!("Error setting initiative draws: {e}")];
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let type_label = ui.label("Damage Type:");
+                ui.add(egui::TextEdit::singleline(&mut damage_type).desired_width(80.0))
+                    .labelled_by(type_label.id);
+
+                let modifiers = [
+                    ("Add Resistance", DamageModifier::Resistance),
+                    ("Add Immunity", DamageModifier::Immunity),
+                    ("Add Vulnerability", DamageModifier::Vulnerability)
+                ];
+                for (label, modifier) in modifiers {
+                    if !ui.button(label).clicked() {
+                        continue;
+                    }
 
+                    let result = match (parse_target(&target_input), damage_type.trim()) {
+                        (None, _) => Err(String::from("Invalid creature number.")),
+                        (_, "") => Err(String::from("Enter a damage type.")),
+                        (Some(target), damage_type) => order.add_damage_modifier(target, modifier, damage_type.to_string())
+                            .map_err(|e| format!("Error adding damage modifier: {e}"))
+                    };
+                    if let Err(e) = result {
+                        updates = vec![e];
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Start Combat").clicked() {
+                    match order.start_combat() {
+                        Ok(combat_updates) => updates = combat_updates,
+                        Err(e) => updates = vec![e]
+                    }
+                }
+                if ui.add_enabled(order.combat_started(), egui::Button::new("End Combat")).clicked() {
+                    match order.end_combat() {
+                        Ok(summary) => updates = summary.to_markdown().lines().map(String::from).collect(),
+                        Err(e) => updates = vec![e.to_string()]
+                    }
                 }
+                if ui.button("Next Turn").clicked() {
+                    match order.next_turn() {
+                        Ok(turn_updates) => updates = turn_updates,
+                        Err(e) => updates = vec![e]
+                    }
+                }
+                let current_system = order.initiative_system().name();
+                egui::ComboBox::from_id_salt("initiative_system")
+                    .selected_text(format!("{current_system} Initiative"))
+                    .show_ui(ui, |ui| {
+                        for name in ["Standard", "Reroll", "Side", "Popcorn", "Cards", "Passes"] {
+                            if ui.selectable_label(current_system == name, name).clicked() {
+                                // Card decks can be seeded so a session's deals can be repeated
+                                let system: Option<Box<dyn initiative::InitiativeSystem>> = match (name, try_get_type::<u64>(&seed_input)) {
+                                    ("Cards", UserInputResult::Ok(seed)) => Some(Box::new(initiative::cards::CardInitiative::with_seed(seed))),
+                                    _ => initiative::system_from_name(name)
+                                };
+                                if let Some(system) = system {
+                                    order.set_initiative_system(system);
+                                }
+                            }
+                        }
+                    });
+                let seed_label = ui.label("Seed:");
+                ui.add(egui::TextEdit::singleline(&mut seed_input).desired_width(80.0).hint_text("random"))
+                    .labelled_by(seed_label.id);
+                ui.checkbox(&mut player_view.open, "Player View");
+                let mut auto_death_saves = order.auto_death_saves();
+                if ui.checkbox(&mut auto_death_saves, "Auto-roll Death Saves").changed() {
+                    order.set_auto_death_saves(auto_death_saves);
+                }
+                if order.combat_started() {
+                    ui.label(order.round_display());
+                }
+            });
+            for update in &updates {
+                ui.label(update);
             }
-            for creature in order.creatures() {
-                ui.label(format!("{creature}"));
+            egui::CollapsingHeader::new("Save and Load").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let path_label = ui.label("File:");
+                    ui.text_edit_singleline(&mut file_path)
+                        .labelled_by(path_label.id);
+                    egui::ComboBox::from_id_salt("file_format")
+                        .selected_text(file_format.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in Format::ALL {
+                                ui.selectable_value(&mut file_format, option, option.to_string());
+                            }
+                        });
+                    if ui.button("Export").clicked() {
+                        updates = match order.export(Path::new(&file_path), file_format) {
+                            Ok(unsupported) => std::iter::once(format!("Saved as {file_format}."))
+                                .chain(unsupported.into_iter().map(|field| format!("Not saved: {field}")))
+                                .collect(),
+                            Err(e) => vec![format!("Error saving turn order: {e}")]
+                        };
+                    }
+                    if ui.button("Import").clicked() {
+                        match TurnOrder::import(Path::new(&file_path), file_format) {
+                            Ok(converted) => {
+                                updates = converted.unsupported().iter().map(|field| format!("Not loaded: {field}")).collect();
+                                replace_order(order, converted.into_value());
+                            }
+                            Err(e) => updates = vec![format!("Error loading turn order: {e}")]
+                        }
+                    }
+                });
+            });
+            egui::CollapsingHeader::new("Prepared Encounters").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut include_roster, "Include Party Roster");
+                    if ui.button("Reload Library").clicked() {
+                        match EncounterLibrary::load() {
+                            Ok(library) => encounters = library,
+                            Err(e) => updates = vec![format!("Error loading encounter library: {e}")]
+                        }
+                    }
+                });
+                #[cfg(feature = "scripting")]
+                if ui.button("Reload Scripts").clicked() {
+                    match order.scripts_mut().load() {
+                        Ok(count) => updates = vec![format!("Loaded {count} script(s).")],
+                        Err(e) => updates = vec![format!("Error loading scripts: {e}")]
+                    }
+                }
+                for encounter in encounters.encounters() {
+                    ui.horizontal(|ui| {
+                        if ui.button("Load").clicked() {
+                            if include_roster {
+                                for template in roster.templates() {
+                                    order.add_creature_from_template(template, template.roll_initiative());
+                                }
+                            }
+                            order.load_encounter(encounter);
+                        }
+                        ui.label(encounter.to_string());
+                    });
+                }
+            });
+            ui.separator();
+
+            // Rows can be dragged onto another row to manually reorder
+            let mut actions: Vec<RowAction> = Vec::new();
+
+            for (index, entry) in order.entries().iter().enumerate() {
+                let current = if index == order.current_turn() { "▶ " } else { "" };
+                let manual = if entry.manually_placed() { " [MANUAL]" } else { "" };
+                let hidden_mark = if entry.hidden() { " [HIDDEN]" } else { "" };
+                let initiative = order.initiative_system().initiative_display(entry);
+                let entry_num = index + 1;
+
+                ui.horizontal(|ui| {
+                    let row = ui.dnd_drag_source(egui::Id::new(("creature_row", index)), index, |ui| {
+                        let text = egui::RichText::new(format!("{current}[{entry_num}] {initiative}{manual}{hidden_mark} {entry}"));
+                        match entry.kind() {
+                            Some(kind) => ui.label(text.color(kind_color(kind))),
+                            None => ui.label(text)
+                        };
+                    }).response;
+
+                    if let Some(from) = row.dnd_release_payload::<usize>() {
+                        actions.push(RowAction::Move(*from, index));
+                    }
+
+                    if let Some(creature) = entry.as_creature() {
+                        creature_controls(ui, creature, Target::entry(index), &mut actions);
+                    }
+
+                    if let Some(group) = entry.as_group() {
+                        let toggle = if group.expanded() { "Collapse" } else { "Expand" };
+                        if ui.button(toggle).clicked() {
+                            actions.push(RowAction::Expand(index, !group.expanded()));
+                        }
+                    }
+
+                    if order.initiative_system().uses_cards() && ui.button("Redraw").clicked() {
+                        actions.push(RowAction::Redraw(index));
+                    }
+
+                    // The last to act can also pick itself to start the next round
+                    let last_to_act = order.current_turn() + 1 == order.entries().len();
+                    if order.initiative_system().hands_off() && (index != order.current_turn() || last_to_act) && ui.button("Goes Next").clicked() {
+                        actions.push(RowAction::HandOff(index));
+                    }
+                });
+
+                if let Some(group) = entry.as_group().filter(|group| group.expanded()) {
+                    ui.indent(("group_members", index), |ui| {
+                        for (member_index, member) in group.members().iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let text = egui::RichText::new(format!("[{entry_num}.{}] {member}", member_index + 1));
+                                ui.label(text.color(kind_color(member.kind())));
+                                creature_controls(ui, member, Target::member(index, member_index), &mut actions);
+                            });
+                        }
+                    });
+                }
+            }
+
+            for action in actions {
+                let result = match action {
+                    RowAction::Move(from, to) => order.move_creature(from, to).map(|_| None),
+                    RowAction::Expand(index, expanded) => order.set_group_expanded(index, expanded).map(|_| None),
+                    RowAction::Hide(index, true) => order.set_creature_hidden(index, true).map(|_| None),
+                    RowAction::Hide(index, false) => order.reveal_creature(index).map(Some),
+                    RowAction::Surprise(index, surprised) => order.set_surprised(index, surprised).map(|_| None),
+                    RowAction::HideEffect(index, effect_index, hidden) => {
+                        order.set_status_effect_hidden(index, effect_index, hidden).map(|_| None)
+                    }
+                    RowAction::AdjustCondition(target, effect_index, change) => {
+                        order.adjust_condition_value(target, effect_index, change).map(Some)
+                    }
+                    RowAction::SpendLegendaryAction(index) => order.spend_legendary_action(index).map(Some),
+                    RowAction::SpendReaction(target) => order.spend_reaction(target).map(Some),
+                    RowAction::SpendBonusAction(target) => order.spend_bonus_action(target).map(Some),
+                    // Movement is spent from the shared amount field
+                    RowAction::SpendMovement(target) => match try_get_type::<usize>(&amount_input) {
+                        UserInputResult::Ok(distance) => order.spend_movement(target, distance).map(Some),
+                        _ => Err("Enter the distance moved in the amount field.")
+                    },
+                    RowAction::HandOff(index) => {
+                        updates = order.hand_off(index).unwrap_or_else(|e| vec![e]);
+                        Ok(None)
+                    }
+                    RowAction::Redraw(index) => order.redraw_initiative(index).map(Some),
+                    RowAction::SetKind(target, kind) => order.set_kind(target, kind).map(|_| None),
+                    RowAction::SetDeathSaves(target, uses_death_saves) => {
+                        order.set_uses_death_saves(target, uses_death_saves).map(|_| None)
+                    }
+                    // The d20 roll is also taken from the amount field
+                    RowAction::RecordDeathSave(target) => match try_get_type::<usize>(&amount_input) {
+                        UserInputResult::Ok(roll) => order.record_death_save(target, roll).map(|save_updates| Some(save_updates.join(" "))),
+                        _ => Err("Enter the d20 roll in the amount field.")
+                    }
+                };
+
+                match result {
+                    Ok(Some(update)) => updates = vec![update],
+                    Ok(None) => (),
+                    Err(e) => updates = vec![format!("Error: {e}")]
+                }
             }
+
+            #[cfg(feature = "scripting")]
+            updates.append(&mut order.take_script_messages());
         });
+
+        player_view.show(ctx, session.current());
+
+        // The unfinished autosave is left alone until the user decides what to do with it
+        if let Some(autosave) = autosave.as_mut().filter(|_| unfinished.is_none()) {
+            if ctx.input(|input| input.viewport().close_requested()) {
+                if let Err(e) = autosave.clear() {
+                    eprintln!("Error clearing autosave: {e}");
+                }
+            }
+            else if let Err(e) = autosave.save(&session) {
+                updates = vec![format!("Error autosaving: {e}")];
+            }
+            ctx.request_repaint_after(autosave_interval);
+        }
     })
 }
 
+// Loaded scripts carry over to the new order
+fn replace_order(order: &mut TurnOrder, turn_order: TurnOrder) {
+    #[cfg(feature = "scripting")]
+    let scripts = std::mem::take(order.scripts_mut());
+    *order = turn_order;
+    #[cfg(feature = "scripting")]
+    {
+        *order.scripts_mut() = scripts;
+    }
+}
+
+fn replace_session(session: &mut Session, resumed: Session) {
+    #[cfg(feature = "scripting")]
+    let scripts = std::mem::take(session.current_mut().scripts_mut());
+    *session = resumed;
+    #[cfg(feature = "scripting")]
+    {
+        *session.current_mut().scripts_mut() = scripts;
+    }
+}
+
 fn try_get_type<T>(user_input: &str) -> UserInputResult<T, String> 
 where
     T: str::FromStr,
@@ -89,3 +714,111 @@ enum UserInputResult<T, E> {
     None
 }
 
+// Parses "3" for an entry or "3.2" for the second member of group 3, both 1 based
+fn parse_target(user_input: &str) -> Option<Target> {
+    let parse_num = |num: &str| num.trim().parse::<usize>().ok().filter(|num| *num > 0).map(|num| num - 1);
+
+    match user_input.split_once('.') {
+        Some((entry, member)) => Some(Target::member(parse_num(entry)?, parse_num(member)?)),
+        None => Some(Target::entry(parse_num(user_input)?))
+    }
+}
+
+// Per-creature controls, shared by single creatures and group members
+fn creature_controls(ui: &mut egui::Ui, creature: &Creature, target: Target, actions: &mut Vec<RowAction>) {
+    let mut kind = creature.kind();
+    if kind_combo_box(ui, ("kind", target.index(), target.member_num()), &mut kind) {
+        actions.push(RowAction::SetKind(target, kind));
+    }
+
+    let mut hidden = creature.hidden();
+    if ui.checkbox(&mut hidden, "Hidden").changed() {
+        actions.push(RowAction::Hide(target, hidden));
+    }
+
+    let mut surprised = creature.surprised();
+    if ui.checkbox(&mut surprised, "Surprised").changed() {
+        actions.push(RowAction::Surprise(target, surprised));
+    }
+
+    if creature.legendary_actions().is_some() && ui.button("Use LA").clicked() {
+        actions.push(RowAction::SpendLegendaryAction(target));
+    }
+
+    let action_economy = creature.action_economy();
+    if ui.add_enabled(action_economy.reaction_available(), egui::Button::new("Reaction")).clicked() {
+        actions.push(RowAction::SpendReaction(target));
+    }
+    if ui.add_enabled(action_economy.bonus_action_available(), egui::Button::new("Bonus")).clicked() {
+        actions.push(RowAction::SpendBonusAction(target));
+    }
+    if action_economy.movement().is_some() && ui.button("Move").clicked() {
+        actions.push(RowAction::SpendMovement(target));
+    }
+
+    let mut uses_death_saves = creature.uses_death_saves();
+    if ui.checkbox(&mut uses_death_saves, "Death Saves").changed() {
+        actions.push(RowAction::SetDeathSaves(target, uses_death_saves));
+    }
+    if creature.is_dying() && ui.button("Death Save").clicked() {
+        actions.push(RowAction::RecordDeathSave(target));
+    }
+
+    for (effect_index, effect) in creature.status_effects().iter().enumerate() {
+        let mut effect_hidden = effect.hidden();
+        if ui.checkbox(&mut effect_hidden, format!("Hide {}", effect.name())).changed() {
+            actions.push(RowAction::HideEffect(target, effect_index, effect_hidden));
+        }
+        if effect.value().is_some() {
+            if ui.small_button("-").clicked() {
+                actions.push(RowAction::AdjustCondition(target, effect_index, -1));
+            }
+            if ui.small_button("+").clicked() {
+                actions.push(RowAction::AdjustCondition(target, effect_index, 1));
+            }
+        }
+    }
+}
+
+// Returns true when a different kind was picked
+fn kind_combo_box(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, kind: &mut CreatureKind) -> bool {
+    let previous = *kind;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(kind.to_string())
+        .show_ui(ui, |ui| {
+            for option in CreatureKind::ALL {
+                ui.selectable_value(kind, option, option.to_string());
+            }
+        });
+
+    *kind != previous
+}
+
+fn kind_color(kind: CreatureKind) -> egui::Color32 {
+    match kind {
+        CreatureKind::PlayerCharacter => egui::Color32::LIGHT_GREEN,
+        CreatureKind::Ally => egui::Color32::LIGHT_BLUE,
+        CreatureKind::Neutral => egui::Color32::GRAY,
+        CreatureKind::Enemy => egui::Color32::LIGHT_RED,
+        CreatureKind::Environment => egui::Color32::from_rgb(210, 170, 110)
+    }
+}
+
+// Changes requested while drawing the rows, applied once the order is no longer borrowed
+enum RowAction {
+    Move(usize, usize),
+    Expand(usize, bool),
+    Hide(Target, bool),
+    Surprise(Target, bool),
+    HideEffect(Target, usize, bool),
+    AdjustCondition(Target, usize, isize),
+    SpendLegendaryAction(Target),
+    SpendReaction(Target),
+    SpendBonusAction(Target),
+    SpendMovement(Target),
+    HandOff(usize),
+    Redraw(usize),
+    SetKind(Target, CreatureKind),
+    SetDeathSaves(Target, bool),
+    RecordDeathSave(Target)
+}
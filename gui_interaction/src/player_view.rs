@@ -0,0 +1,76 @@
+use eframe::egui;
+use turn_order::turn_order::TurnOrder;
+
+const PLAYER_FONT_SIZE: f32 = 32.0;
+
+/*
+    A second window meant for a TV or player-facing screen. Shows only
+    the round (and pass), the initiative order, the current turn and
+    effects that aren't hidden, leaving out hidden creatures entirely.
+ */
+#[derive(Default)]
+pub struct PlayerView {
+    pub open: bool,
+    // Only scroll when the turn changes so the window can still be scrolled by hand
+    last_scrolled_turn: Option<usize>
+}
+
+impl PlayerView {
+    pub fn show(&mut self, ctx: &egui::Context, order: &TurnOrder) {
+        if !self.open {
+            self.last_scrolled_turn = None;
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("player_view"),
+            egui::ViewportBuilder::default()
+                .with_title("Initiative Tracker - Player View")
+                .with_inner_size([800.0, 600.0]),
+            |ctx, _class| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    if order.combat_started() {
+                        ui.label(egui::RichText::new(order.round_display()).size(PLAYER_FONT_SIZE).strong());
+                        ui.separator();
+                    }
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (index, entry) in order.entries().iter().enumerate() {
+                            if entry.hidden() {
+                                continue;
+                            }
+
+                            let initiative = order.initiative_system().initiative_display(entry);
+                            let mut display = entry.public_display();
+                            if let Some(group) = entry.as_group().filter(|group| group.expanded()) {
+                                for member in group.members().iter().filter(|member| !member.hidden()) {
+                                    display.push_str(&format!("\n      {}", member.public_display()));
+                                }
+                            }
+                            let current = index == order.current_turn();
+
+                            let mut text = egui::RichText::new(format!("{initiative}  {display}"))
+                                .size(PLAYER_FONT_SIZE);
+                            if current {
+                                text = egui::RichText::new(format!("▶ {initiative}  {display}"))
+                                    .size(PLAYER_FONT_SIZE)
+                                    .strong()
+                                    .color(egui::Color32::YELLOW);
+                            }
+
+                            let label = ui.label(text);
+                            if current && self.last_scrolled_turn != Some(index) {
+                                label.scroll_to_me(Some(egui::Align::Center));
+                                self.last_scrolled_turn = Some(index);
+                            }
+                        }
+                    });
+                });
+
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.open = false;
+                }
+            }
+        );
+    }
+}
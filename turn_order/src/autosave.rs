@@ -0,0 +1,219 @@
+use crate::storage;
+use crate::session::Session;
+use std::{fs, io, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
+
+const AUTOSAVE_FILE: &str = "autosave.json";
+
+/*
+    Keeps a copy of the session in the data directory while the
+    tracker runs, so a crash doesn't lose the fight. The file is removed
+    when the tracker closes normally, so one still there at launch means
+    the last session didn't finish and can be resumed.
+ */
+pub struct Autosave {
+    path: PathBuf,
+    interval: Duration,
+    last_saved: Option<Instant>,
+    // What was last written, so an unchanged order isn't written again
+    last_contents: String,
+    // When this autosave last wrote the file, to notice when something else has since
+    last_modified: Option<SystemTime>
+}
+
+impl Autosave {
+    pub fn new() -> Result<Autosave, String> {
+        Ok(Autosave::at(storage::data_file(AUTOSAVE_FILE)?))
+    }
+
+    pub fn at(path: PathBuf) -> Autosave {
+        Autosave { path, interval: Duration::ZERO, last_saved: None, last_contents: String::new(), last_modified: None }
+    }
+
+    // Saves at most once per interval, for frontends that would otherwise save every frame
+    pub fn with_interval(mut self, interval: Duration) -> Autosave {
+        self.interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // What was left behind by a session that didn't close normally, if there was one
+    pub fn unfinished(&self) -> Result<Option<Session>, String> {
+        match self.path.exists() {
+            true => Session::load_from(&self.path).map(Some),
+            false => Ok(None)
+        }
+    }
+
+    // A session with no creatures has nothing worth recovering, so its autosave is removed instead
+    pub fn save(&mut self, session: &Session) -> Result<(), String> {
+        if self.last_saved.is_some_and(|last_saved| last_saved.elapsed() < self.interval) {
+            return Ok(());
+        }
+        self.last_saved = Some(Instant::now());
+
+        if session.is_empty() {
+            return self.clear();
+        }
+
+        let contents = serde_json::to_string(session).map_err(|e| e.to_string())?;
+        if contents == self.last_contents {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+        }
+        storage::write_atomic(&self.path, &contents)?;
+        self.last_contents = contents;
+        self.last_modified = self.modified();
+
+        Ok(())
+    }
+
+    /*
+        The session as something else, such as the web server, last
+        wrote it, if the file has changed since this autosave last saved
+        it. Only checked once this autosave has saved, so an unfinished
+        session the user chose not to resume isn't picked up.
+     */
+    pub fn changed_elsewhere(&mut self) -> Result<Option<Session>, String> {
+        let modified = self.modified();
+        if self.last_saved.is_none() || modified.is_none() || modified == self.last_modified {
+            return Ok(None);
+        }
+        self.last_modified = modified;
+
+        let contents = fs::read_to_string(&self.path).map_err(|e| format!("Could not read {}: {e}", self.path.display()))?;
+        if contents == self.last_contents {
+            return Ok(None);
+        }
+        let session = Session::load_from(&self.path)?;
+        self.last_contents = contents;
+
+        Ok(Some(session))
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    // Called when the tracker closes normally
+    pub fn clear(&mut self) -> Result<(), String> {
+        self.last_contents.clear();
+        self.last_modified = None;
+
+        match fs::remove_file(&self.path) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Could not remove {}: {e}", self.path.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::initiative::cards::CardInitiative;
+    use std::env;
+
+    // Each test gets its own file so they can run in parallel
+    fn path(test: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("turn_order_autosave_{}_{test}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn first_name(session: &Session) -> &str {
+        session.current().entries()[0].name()
+    }
+
+    #[test]
+    fn saves_until_cleared() {
+        let mut autosave = Autosave::at(path("cycle"));
+        assert!(autosave.unfinished().unwrap().is_none());
+
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        autosave.save(&session).unwrap();
+        let resumed = autosave.unfinished().unwrap().expect("autosave should be left behind");
+        assert_eq!(first_name(&resumed), "Aria");
+
+        autosave.clear().unwrap();
+        assert!(!autosave.path().exists());
+        assert!(autosave.unfinished().unwrap().is_none());
+        autosave.clear().unwrap();
+    }
+
+    #[test]
+    fn empty_session_removes_the_autosave() {
+        let mut autosave = Autosave::at(path("empty"));
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        autosave.save(&session).unwrap();
+
+        session.current_mut().remove_creature(0).unwrap();
+        autosave.save(&session).unwrap();
+        assert!(!autosave.path().exists());
+    }
+
+    #[test]
+    fn saves_at_most_once_per_interval() {
+        let mut autosave = Autosave::at(path("interval")).with_interval(Duration::from_secs(3600));
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        autosave.save(&session).unwrap();
+        session.current_mut().add_creature(String::from("Brom"), 15);
+        autosave.save(&session).unwrap();
+
+        let resumed = autosave.unfinished().unwrap().unwrap();
+        assert_eq!(resumed.current().entries().len(), 1);
+        autosave.clear().unwrap();
+    }
+
+    #[test]
+    fn resumes_with_the_same_initiative_system() {
+        let mut autosave = Autosave::at(path("system"));
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        session.current_mut().set_initiative_system(Box::new(CardInitiative::with_seed(9)));
+        session.current_mut().start_combat().unwrap();
+        autosave.save(&session).unwrap();
+
+        let resumed = autosave.unfinished().unwrap().unwrap();
+        assert_eq!(resumed.current().initiative_system().name(), "Cards");
+        assert_eq!(resumed.current().entries()[0].initiative(), session.current().entries()[0].initiative());
+        autosave.clear().unwrap();
+    }
+
+    #[test]
+    fn picks_up_a_session_written_elsewhere() {
+        let mut autosave = Autosave::at(path("elsewhere"));
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        autosave.save(&session).unwrap();
+        assert!(autosave.changed_elsewhere().unwrap().is_none());
+
+        // File timestamps are coarse, so the other write is spaced out to be seen as a change
+        std::thread::sleep(Duration::from_millis(50));
+        session.current_mut().add_creature(String::from("Brom"), 15);
+        session.save_to(autosave.path()).unwrap();
+        let changed = autosave.changed_elsewhere().unwrap().expect("the other write should be picked up");
+        assert_eq!(first_name(&changed), "Brom");
+        assert!(autosave.changed_elsewhere().unwrap().is_none());
+        autosave.clear().unwrap();
+    }
+
+    #[test]
+    fn unfinished_session_is_not_picked_up_as_a_change() {
+        let mut autosave = Autosave::at(path("unfinished"));
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 12);
+        session.save_to(autosave.path()).unwrap();
+
+        assert!(autosave.changed_elsewhere().unwrap().is_none());
+        autosave.clear().unwrap();
+    }
+}
@@ -0,0 +1,338 @@
+use crate::roster::CreatureTemplate;
+use crate::storage;
+use crate::turn_order::creature::{damage_modifiers::{DamageModifier, DamageModifiers}, kind::CreatureKind};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{fs, path::{Path, PathBuf}};
+
+const COMPENDIUM_DIR: &str = "compendium";
+
+/*
+    Monster stat blocks read from local JSON files in the open 5e SRD
+    formats. Only the fields the tracker uses are kept, anything else
+    in the files is ignored.
+ */
+#[derive(Default)]
+pub struct Compendium {
+    monsters: Vec<MonsterStatBlock>
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MonsterStatBlock {
+    name: String,
+    #[serde(default = "average_ability_score")]
+    dexterity: isize,
+    #[serde(default)]
+    hit_points: Option<usize>,
+    #[serde(default, deserialize_with = "condition_names")]
+    condition_immunities: Vec<String>,
+    #[serde(default, deserialize_with = "condition_names")]
+    damage_resistances: Vec<String>,
+    #[serde(default, deserialize_with = "condition_names")]
+    damage_immunities: Vec<String>,
+    #[serde(default, deserialize_with = "condition_names")]
+    damage_vulnerabilities: Vec<String>
+}
+
+// How many monsters an import added, and the files it skipped with why
+#[derive(Default)]
+pub struct ImportReport {
+    count: usize,
+    skipped: Vec<String>
+}
+
+// A file can hold one monster, a list of them, or an API page of results
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MonsterFile {
+    Many(Vec<MonsterStatBlock>),
+    Results { results: Vec<MonsterStatBlock> },
+    One(MonsterStatBlock)
+}
+
+// Conditions and damage types are either a comma separated string or a list of names or references
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConditionList {
+    Text(String),
+    List(Vec<ConditionName>)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConditionName {
+    Name(String),
+    Reference { name: String }
+}
+
+fn average_ability_score() -> isize {
+    10
+}
+
+fn condition_names<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let names = match ConditionList::deserialize(deserializer)? {
+        ConditionList::Text(text) => text.split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        ConditionList::List(names) => names.into_iter()
+            .map(|name| match name {
+                ConditionName::Name(name) => name,
+                ConditionName::Reference { name } => name
+            })
+            .collect()
+    };
+
+    Ok(names)
+}
+
+impl Compendium {
+    pub fn new() -> Compendium {
+        Compendium::default()
+    }
+
+    // Reads every file in the compendium folder of the data directory, empty if there isn't one
+    pub fn load() -> Result<(Compendium, ImportReport), String> {
+        let mut compendium = Compendium::new();
+        let dir = storage::data_file(COMPENDIUM_DIR)?;
+        let report = match dir.is_dir() {
+            true => compendium.import(&dir)?,
+            false => ImportReport::default()
+        };
+
+        Ok((compendium, report))
+    }
+
+    /*
+        Imports a JSON file, or every JSON file in a folder. A file in a
+        folder that can't be read is skipped and listed in the report,
+        so one bad file doesn't stop the rest.
+     */
+    pub fn import(&mut self, path: &Path) -> Result<ImportReport, String> {
+        if !path.is_dir() {
+            let count = self.import_file(path)?;
+            return Ok(ImportReport { count, skipped: Vec::new() });
+        }
+
+        let entries = fs::read_dir(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let mut files: Vec<PathBuf> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|file| file.extension().is_some_and(|extension| extension == "json"))
+            .collect();
+        files.sort();
+
+        let mut report = ImportReport::default();
+        for file in files {
+            match self.import_file(&file) {
+                Ok(count) => report.count += count,
+                Err(e) => report.skipped.push(e)
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn import_file(&mut self, path: &Path) -> Result<usize, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let file: MonsterFile = serde_json::from_str(&contents)
+            .map_err(|e| format!("Could not parse {}: {e}", path.display()))?;
+
+        let monsters = match file {
+            MonsterFile::Many(monsters) => monsters,
+            MonsterFile::Results { results } => results,
+            MonsterFile::One(monster) => vec![monster]
+        };
+
+        let count = monsters.len();
+        for monster in monsters {
+            self.add(monster);
+        }
+
+        Ok(count)
+    }
+
+    pub fn monsters(&self) -> &Vec<MonsterStatBlock> {
+        &self.monsters
+    }
+
+    // Names are compared ignoring case
+    pub fn get(&self, name: &str) -> Option<&MonsterStatBlock> {
+        self.monsters.iter().find(|monster| monster.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    // Replaces any monster with the same name
+    pub fn add(&mut self, monster: MonsterStatBlock) {
+        self.monsters.retain(|existing| !existing.name.eq_ignore_ascii_case(&monster.name));
+        self.monsters.push(monster);
+    }
+}
+
+impl ImportReport {
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn skipped(&self) -> &Vec<String> {
+        &self.skipped
+    }
+}
+
+impl MonsterStatBlock {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dexterity(&self) -> isize {
+        self.dexterity
+    }
+
+    pub fn hit_points(&self) -> Option<usize> {
+        self.hit_points
+    }
+
+    pub fn condition_immunities(&self) -> &Vec<String> {
+        &self.condition_immunities
+    }
+
+    pub fn damage_modifiers(&self) -> DamageModifiers {
+        let mut damage_modifiers = DamageModifiers::new();
+        damage_modifiers.set(DamageModifier::Resistance, self.damage_resistances.clone());
+        damage_modifiers.set(DamageModifier::Immunity, self.damage_immunities.clone());
+        damage_modifiers.set(DamageModifier::Vulnerability, self.damage_vulnerabilities.clone());
+
+        damage_modifiers
+    }
+
+    // The dexterity modifier, rounding down for odd scores below 10
+    pub fn initiative_modifier(&self) -> isize {
+        (self.dexterity - 10).div_euclid(2)
+    }
+
+    pub fn to_template(&self) -> CreatureTemplate {
+        let mut template = CreatureTemplate::new(self.name.clone(), self.initiative_modifier());
+        template.set_kind(CreatureKind::Enemy);
+        template.set_max_hit_points(self.hit_points);
+        template.set_condition_immunities(self.condition_immunities.clone());
+        template.set_damage_modifiers(self.damage_modifiers());
+
+        template
+    }
+}
+
+/*
+    Splits input like "Goblin x4" into the name and count, a name
+    without a count is a single creature.
+ */
+pub fn parse_name_and_count(input: &str) -> (&str, usize) {
+    let input = input.trim();
+    let count = input.rsplit_once(' ')
+        .and_then(|(name, count)| Some((name.trim(), count.strip_prefix(['x', 'X'])?.parse::<usize>().ok()?)))
+        .filter(|(_, count)| *count > 0);
+
+    count.unwrap_or((input, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::TurnOrder;
+    use std::env;
+
+    // Trimmed from the 5e SRD API, which lists conditions as references
+    const SRD_API_GOBLIN: &str = r#"{
+        "index": "goblin",
+        "name": "Goblin",
+        "dexterity": 14,
+        "hit_points": 7,
+        "damage_vulnerabilities": [],
+        "damage_resistances": [],
+        "damage_immunities": [],
+        "condition_immunities": [],
+        "url": "/api/monsters/goblin"
+    }"#;
+
+    // Trimmed from an Open5e results page, which uses comma separated text
+    const OPEN5E_PAGE: &str = r#"{
+        "count": 1,
+        "results": [{
+            "slug": "skeleton",
+            "name": "Skeleton",
+            "dexterity": 15,
+            "hit_points": 13,
+            "damage_vulnerabilities": "bludgeoning",
+            "damage_resistances": "",
+            "damage_immunities": "poison",
+            "condition_immunities": "exhaustion, poisoned"
+        }]
+    }"#;
+
+    fn folder(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("turn_order_compendium_{}_{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn reads_the_5e_srd_api_format() {
+        let dir = folder("srd_api", &[("goblin.json", SRD_API_GOBLIN)]);
+        let mut compendium = Compendium::new();
+        compendium.import(&dir.join("goblin.json")).unwrap();
+
+        let goblin = compendium.get("goblin").unwrap();
+        assert_eq!(goblin.hit_points(), Some(7));
+        assert_eq!(goblin.initiative_modifier(), 2);
+
+        let reference = r#"{"name": "Lich", "condition_immunities": [{"index": "charmed", "name": "Charmed", "url": "/api/conditions/charmed"}]}"#;
+        let lich: MonsterStatBlock = serde_json::from_str(reference).unwrap();
+        assert_eq!(lich.condition_immunities(), &["Charmed"]);
+        assert_eq!(lich.dexterity(), 10);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reads_open5e_result_pages() {
+        let dir = folder("open5e", &[("page.json", OPEN5E_PAGE)]);
+        let mut compendium = Compendium::new();
+        compendium.import(&dir).unwrap();
+
+        let skeleton = compendium.get("Skeleton").unwrap();
+        assert_eq!(skeleton.condition_immunities(), &["exhaustion", "poisoned"]);
+        let damage_modifiers = skeleton.damage_modifiers();
+        assert_eq!(damage_modifiers.apply(10, "poison").0, 0);
+        assert_eq!(damage_modifiers.apply(10, "bludgeoning").0, 20);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn bad_files_in_a_folder_are_skipped() {
+        let dir = folder("skipped", &[("a.json", SRD_API_GOBLIN), ("b.json", "{ not json"), ("c.json", OPEN5E_PAGE), ("notes.txt", "")]);
+        let mut compendium = Compendium::new();
+
+        let report = compendium.import(&dir).unwrap();
+        assert_eq!(report.count(), 2);
+        assert_eq!(report.skipped().len(), 1);
+        assert!(report.skipped()[0].contains("b.json"));
+        assert_eq!(compendium.monsters().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn imported_condition_immunities_are_enforced() {
+        let skeleton: MonsterFile = serde_json::from_str(OPEN5E_PAGE).unwrap();
+        let MonsterFile::Results { results } = skeleton else {
+            panic!("an Open5e page should read as results");
+        };
+
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature_from_template(&results[0].to_template(), 12);
+        assert!(turn_order.add_status_effect(0, String::from("Poisoned")).is_err());
+        turn_order.add_status_effect(0, String::from("Prone")).unwrap();
+
+        let effects: Vec<&str> = turn_order.creatures().next().unwrap().status_effects().iter().map(|effect| effect.name()).collect();
+        assert_eq!(effects, ["Prone"]);
+    }
+}
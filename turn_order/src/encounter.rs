@@ -0,0 +1,211 @@
+use core::fmt;
+use crate::roster::CreatureTemplate;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const LIBRARY_FILE: &str = "encounters.json";
+
+// Encounters prepared ahead of a session, saved as JSON in the user's data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct EncounterLibrary {
+    encounters: Vec<PreparedEncounter>
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PreparedEncounter {
+    name: String,
+    notes: String,
+    creatures: Vec<EncounterCreature>
+}
+
+/*
+    One line of a prepared encounter. More than one of the same
+    creature are added as a group sharing a single initiative roll.
+ */
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EncounterCreature {
+    template: CreatureTemplate,
+    count: usize,
+    starting_effects: Vec<String>,
+    hidden: bool
+}
+
+impl EncounterLibrary {
+    pub fn new() -> EncounterLibrary {
+        EncounterLibrary::default()
+    }
+
+    // Loads the library from the data directory, empty if none has been saved
+    pub fn load() -> Result<EncounterLibrary, String> {
+        EncounterLibrary::load_from(&storage::data_file(LIBRARY_FILE)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<EncounterLibrary, String> {
+        storage::load_json(path)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&storage::data_file(LIBRARY_FILE)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        storage::save_json(path, self)
+    }
+
+    pub fn encounters(&self) -> &Vec<PreparedEncounter> {
+        &self.encounters
+    }
+
+    // Names are compared ignoring case
+    pub fn get(&self, name: &str) -> Option<&PreparedEncounter> {
+        self.encounters.iter().find(|encounter| encounter.name.eq_ignore_ascii_case(name))
+    }
+
+    // Replaces any encounter with the same name
+    pub fn add(&mut self, encounter: PreparedEncounter) {
+        self.encounters.retain(|existing| !existing.name.eq_ignore_ascii_case(&encounter.name));
+        self.encounters.push(encounter);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<PreparedEncounter, &'static str> {
+        let index = self.encounters.iter().position(|encounter| encounter.name.eq_ignore_ascii_case(name))
+            .ok_or("No encounter with that name in the library.")?;
+
+        Ok(self.encounters.remove(index))
+    }
+}
+
+impl PreparedEncounter {
+    pub fn new(name: String, notes: String) -> PreparedEncounter {
+        PreparedEncounter {
+            name, notes, creatures: Vec::new()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn creatures(&self) -> &Vec<EncounterCreature> {
+        &self.creatures
+    }
+
+    pub fn add_creature(&mut self, creature: EncounterCreature) {
+        self.creatures.push(creature);
+    }
+}
+
+impl EncounterCreature {
+    pub fn new(template: CreatureTemplate, count: usize) -> EncounterCreature {
+        EncounterCreature {
+            template, count: count.max(1), starting_effects: Vec::new(), hidden: false
+        }
+    }
+
+    pub fn template(&self) -> &CreatureTemplate {
+        &self.template
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn starting_effects(&self) -> &Vec<String> {
+        &self.starting_effects
+    }
+
+    pub fn set_starting_effects(&mut self, starting_effects: Vec<String>) {
+        self.starting_effects = starting_effects;
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+}
+
+impl fmt::Display for PreparedEncounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let creatures: Vec<String> = self.creatures.iter()
+            .map(|creature| {
+                let hidden = if creature.hidden { " (hidden)" } else { "" };
+                format!("{} x{}{hidden}", creature.template.name(), creature.count)
+            })
+            .collect();
+
+        write!(f, "{}: {}", self.name, creatures.join(", "))?;
+        if !self.notes.is_empty() {
+            write!(f, " - {}", self.notes)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::TurnOrder;
+    use std::{env, fs};
+
+    fn goblins() -> PreparedEncounter {
+        let mut goblin = CreatureTemplate::new(String::from("Goblin"), 2);
+        goblin.set_max_hit_points(Some(7));
+        let mut goblins = EncounterCreature::new(goblin, 3);
+        goblins.set_starting_effects(vec![String::from("Prone")]);
+
+        let mut encounter = PreparedEncounter::new(String::from("Ambush"), String::from("On the road"));
+        encounter.add_creature(goblins);
+
+        let mut boss = EncounterCreature::new(CreatureTemplate::new(String::from("Bugbear"), 2), 1);
+        boss.set_hidden(true);
+        encounter.add_creature(boss);
+        encounter
+    }
+
+    #[test]
+    fn library_survives_a_save_and_load() {
+        let path = env::temp_dir().join(format!("turn_order_encounters_{}.json", std::process::id()));
+        let mut library = EncounterLibrary::new();
+        library.add(goblins());
+        library.save_to(&path).unwrap();
+
+        let mut loaded = EncounterLibrary::load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get("ambush").unwrap().to_string(), "Ambush: Goblin x3, Bugbear x1 (hidden) - On the road");
+        assert_eq!(loaded.get("Ambush").unwrap().creatures()[0].starting_effects(), &["Prone"]);
+
+        loaded.add(PreparedEncounter::new(String::from("AMBUSH"), String::new()));
+        assert_eq!(loaded.encounters().len(), 1);
+        assert!(loaded.remove("Ambush").is_ok());
+        assert!(loaded.remove("Ambush").is_err());
+    }
+
+    #[test]
+    fn loading_into_a_turn_order_keeps_groups_hidden_creatures_and_effects() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.load_encounter(&goblins());
+
+        let entries = turn_order.entries();
+        assert_eq!(entries.len(), 2);
+
+        let goblins = entries.iter().find_map(|entry| entry.as_group()).unwrap();
+        assert_eq!(goblins.members().len(), 3);
+        for goblin in goblins.members() {
+            assert!(!goblin.hidden());
+            assert_eq!(goblin.status_effects()[0].name(), "Prone");
+        }
+
+        let bugbear = entries.iter().find(|entry| entry.as_group().is_none()).unwrap();
+        assert!(bugbear.hidden());
+        assert_eq!(bugbear.creatures()[0].status_effects().len(), 0);
+    }
+}
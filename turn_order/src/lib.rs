@@ -1 +1,7 @@
-pub mod turn_order;
\ No newline at end of file
+pub mod autosave;
+pub mod compendium;
+pub mod encounter;
+pub mod roster;
+pub mod session;
+pub mod storage;
+pub mod turn_order;
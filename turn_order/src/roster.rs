@@ -0,0 +1,173 @@
+use core::fmt;
+use crate::storage;
+use crate::turn_order::{creature::{damage_modifiers::DamageModifiers, kind::CreatureKind}, dice};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const ROSTER_FILE: &str = "roster.json";
+
+/*
+    Creatures that show up session after session, usually the party.
+    Saved as JSON in the user's data directory.
+ */
+#[derive(Serialize, Deserialize, Default)]
+pub struct Roster {
+    templates: Vec<CreatureTemplate>
+}
+
+// Everything needed to add a creature to a turn order except its rolled initiative
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreatureTemplate {
+    name: String,
+    initiative_modifier: isize,
+    max_hit_points: Option<usize>,
+    passive_perception: Option<usize>,
+    passive_insight: Option<usize>,
+    passive_investigation: Option<usize>,
+    kind: CreatureKind,
+    #[serde(default)]
+    condition_immunities: Vec<String>,
+    #[serde(default)]
+    damage_modifiers: DamageModifiers
+}
+
+impl Roster {
+    pub fn new() -> Roster {
+        Roster::default()
+    }
+
+    // Loads the roster from the data directory, empty if none has been saved
+    pub fn load() -> Result<Roster, String> {
+        Roster::load_from(&storage::data_file(ROSTER_FILE)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Roster, String> {
+        storage::load_json(path)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&storage::data_file(ROSTER_FILE)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        storage::save_json(path, self)
+    }
+
+    pub fn templates(&self) -> &Vec<CreatureTemplate> {
+        &self.templates
+    }
+
+    // Replaces any template with the same name
+    pub fn add(&mut self, template: CreatureTemplate) {
+        self.templates.retain(|existing| existing.name != template.name);
+        self.templates.push(template);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<CreatureTemplate, &'static str> {
+        let index = self.templates.iter().position(|template| template.name == name)
+            .ok_or("No creature with that name in the roster.")?;
+
+        Ok(self.templates.remove(index))
+    }
+}
+
+impl CreatureTemplate {
+    // Templates default to player characters, since that's what rosters are mostly for
+    pub fn new(name: String, initiative_modifier: isize) -> CreatureTemplate {
+        CreatureTemplate {
+            name, initiative_modifier, max_hit_points: None, passive_perception: None,
+            passive_insight: None, passive_investigation: None, kind: CreatureKind::PlayerCharacter,
+            condition_immunities: Vec::new(), damage_modifiers: DamageModifiers::new()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn initiative_modifier(&self) -> isize {
+        self.initiative_modifier
+    }
+
+    pub fn max_hit_points(&self) -> Option<usize> {
+        self.max_hit_points
+    }
+
+    pub fn set_max_hit_points(&mut self, max_hit_points: Option<usize>) {
+        self.max_hit_points = max_hit_points;
+    }
+
+    pub fn passive_perception(&self) -> Option<usize> {
+        self.passive_perception
+    }
+
+    pub fn passive_insight(&self) -> Option<usize> {
+        self.passive_insight
+    }
+
+    pub fn passive_investigation(&self) -> Option<usize> {
+        self.passive_investigation
+    }
+
+    pub fn set_passives(&mut self, perception: Option<usize>, insight: Option<usize>, investigation: Option<usize>) {
+        self.passive_perception = perception;
+        self.passive_insight = insight;
+        self.passive_investigation = investigation;
+    }
+
+    pub fn kind(&self) -> CreatureKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: CreatureKind) {
+        self.kind = kind;
+    }
+
+    pub fn condition_immunities(&self) -> &Vec<String> {
+        &self.condition_immunities
+    }
+
+    pub fn set_condition_immunities(&mut self, condition_immunities: Vec<String>) {
+        self.condition_immunities = condition_immunities;
+    }
+
+    pub fn damage_modifiers(&self) -> &DamageModifiers {
+        &self.damage_modifiers
+    }
+
+    pub fn set_damage_modifiers(&mut self, damage_modifiers: DamageModifiers) {
+        self.damage_modifiers = damage_modifiers;
+    }
+
+    // A d20 plus the initiative modifier
+    pub fn roll_initiative(&self) -> isize {
+        dice::roll_d20() as isize + self.initiative_modifier
+    }
+}
+
+impl fmt::Display for CreatureTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut template_str = format!("{} ({}) Init {:+}", self.name, self.kind, self.initiative_modifier);
+
+        if let Some(max_hit_points) = self.max_hit_points {
+            template_str.push_str(&format!(" HP {max_hit_points}"));
+        }
+
+        let passives = [("PP", self.passive_perception), ("PIns", self.passive_insight), ("PInv", self.passive_investigation)];
+        for (label, passive) in passives {
+            if let Some(passive) = passive {
+                template_str.push_str(&format!(" {label} {passive}"));
+            }
+        }
+
+        if !self.damage_modifiers.is_empty() {
+            template_str.push_str(&format!(" {}", self.damage_modifiers));
+        }
+
+        if !self.condition_immunities.is_empty() {
+            template_str.push_str(&format!(" Immune: {}", self.condition_immunities.join(", ")));
+        }
+
+        write!(f, "{template_str}")
+    }
+}
@@ -0,0 +1,250 @@
+use crate::storage;
+use crate::turn_order::{entry::Target, TurnOrder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SESSION_FILE: &str = "session.json";
+
+/*
+    Several named turn orders run side by side, for when the party
+    splits up and more than one fight is going at once. Commands go to
+    the active one, and creatures can be moved between them. The whole
+    session is saved as one file.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    encounters: Vec<Encounter>,
+    // Always a valid index, a session is never left without an encounter
+    active: usize
+}
+
+#[derive(Serialize, Deserialize)]
+struct Encounter {
+    name: String,
+    turn_order: TurnOrder
+}
+
+impl Session {
+    pub const DEFAULT_NAME: &'static str = "Main";
+
+    pub fn new() -> Session {
+        Session::from_turn_order(String::from(Session::DEFAULT_NAME), TurnOrder::new())
+    }
+
+    pub fn from_turn_order(name: String, turn_order: TurnOrder) -> Session {
+        Session { encounters: vec![Encounter { name, turn_order }], active: 0 }
+    }
+
+    // Loads the session from the data directory, a new one if none has been saved
+    pub fn load() -> Result<Session, String> {
+        Session::load_from(&storage::data_file(SESSION_FILE)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Session, String> {
+        let session: Session = storage::load_json(path)?;
+        match session.active < session.encounters.len() {
+            true => Ok(session),
+            false => Err(format!("{} has no active encounter.", path.display()))
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&storage::data_file(SESSION_FILE)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        storage::save_json(path, self)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.encounters.iter().map(|encounter| encounter.name.as_str())
+    }
+
+    pub fn turn_orders(&self) -> impl Iterator<Item = &TurnOrder> {
+        self.encounters.iter().map(|encounter| &encounter.turn_order)
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.encounters[self.active].name
+    }
+
+    pub fn current(&self) -> &TurnOrder {
+        &self.encounters[self.active].turn_order
+    }
+
+    pub fn current_mut(&mut self) -> &mut TurnOrder {
+        &mut self.encounters[self.active].turn_order
+    }
+
+    pub fn into_current(mut self) -> TurnOrder {
+        self.encounters.swap_remove(self.active).turn_order
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turn_orders().all(|turn_order| turn_order.entries().is_empty())
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.encounters.iter().position(|encounter| encounter.name.eq_ignore_ascii_case(name))
+    }
+
+    // Adds an empty encounter without switching to it, returning its index
+    pub fn add(&mut self, name: String) -> Result<usize, &'static str> {
+        if name.trim().is_empty() {
+            return Err("Encounters need a name.");
+        }
+        if self.position(&name).is_some() {
+            return Err("There is already an encounter with that name.");
+        }
+
+        self.encounters.push(Encounter { name: name.trim().to_string(), turn_order: TurnOrder::new() });
+        Ok(self.encounters.len() - 1)
+    }
+
+    pub fn switch(&mut self, name: &str) -> Result<(), &'static str> {
+        let index = self.position(name).ok_or("No encounter with that name.")?;
+        self.switch_to(index)
+    }
+
+    pub fn switch_to(&mut self, index: usize) -> Result<(), &'static str> {
+        if index >= self.encounters.len() {
+            return Err("Encounter index out of range.");
+        }
+
+        // Loaded scripts follow whichever encounter is being run
+        #[cfg(feature = "scripting")]
+        {
+            let scripts = std::mem::take(self.current_mut().scripts_mut());
+            *self.encounters[index].turn_order.scripts_mut() = scripts;
+        }
+
+        self.active = index;
+        Ok(())
+    }
+
+    // Switches to the previous encounter if the active one is removed
+    pub fn remove(&mut self, name: &str) -> Result<TurnOrder, &'static str> {
+        let index = self.position(name).ok_or("No encounter with that name.")?;
+        if self.encounters.len() == 1 {
+            return Err("A session needs at least one encounter.");
+        }
+
+        if index == self.active {
+            let neighbour = if index == 0 { 1 } else { index - 1 };
+            self.switch_to(neighbour)?;
+        }
+        if index < self.active {
+            self.active -= 1;
+        }
+
+        Ok(self.encounters.remove(index).turn_order)
+    }
+
+    /*
+        Moves a creature, group or group member from the active encounter
+        to another one, keeping its initiative, HP and status effects.
+     */
+    pub fn move_creature(&mut self, target: impl Into<Target>, to: &str) -> Result<String, &'static str> {
+        let to = self.position(to).ok_or("No encounter with that name.")?;
+        if to == self.active {
+            return Err("The creature is already in that encounter.");
+        }
+
+        let entry = self.current_mut().take_creature(target)?;
+        let update = format!("Moved {} to {}.", entry.name(), self.encounters[to].name);
+        self.encounters[to].turn_order.insert_creature(entry);
+
+        Ok(update)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(turn_order: &TurnOrder) -> Vec<&str> {
+        turn_order.entries().iter().map(|entry| entry.name()).collect()
+    }
+
+    #[test]
+    fn transfer_keeps_hit_points_and_effects() {
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 18);
+        session.current_mut().add_group(String::from("Goblin"), 12, 2);
+        session.current_mut().set_max_hit_points(0, 30).unwrap();
+        session.current_mut().damage(0, 6).unwrap();
+        session.current_mut().add_status_effect(0, String::from("Blessed")).unwrap();
+        session.add(String::from("Cellar")).unwrap();
+
+        assert_eq!(session.move_creature(0, "cellar").unwrap(), "Moved Aria to Cellar.");
+        assert_eq!(names(session.current()), ["Goblin"]);
+
+        session.switch("Cellar").unwrap();
+        let aria = session.current().creatures().next().unwrap();
+        assert_eq!(aria.initiative(), 18);
+        assert_eq!(aria.hit_points().map(|hit_points| hit_points.current()), Some(24));
+        assert_eq!(aria.status_effects()[0].name(), "Blessed");
+    }
+
+    #[test]
+    fn transfer_slots_into_the_other_initiative_order() {
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 18);
+        session.add(String::from("Cellar")).unwrap();
+        session.switch("Cellar").unwrap();
+        session.current_mut().add_creature(String::from("Rat"), 20);
+        session.current_mut().add_creature(String::from("Spider"), 9);
+        session.switch(Session::DEFAULT_NAME).unwrap();
+
+        session.move_creature(0, "Cellar").unwrap();
+        session.switch("Cellar").unwrap();
+        assert_eq!(names(session.current()), ["Rat", "Aria", "Spider"]);
+    }
+
+    #[test]
+    fn transfer_can_split_a_group() {
+        let mut session = Session::new();
+        session.current_mut().add_group(String::from("Goblin"), 12, 2);
+        session.add(String::from("Cellar")).unwrap();
+
+        session.move_creature(Target::member(0, 1), "Cellar").unwrap();
+        let group = session.current().entries()[0].as_group().unwrap();
+        assert_eq!(group.members().len(), 1);
+
+        session.switch("Cellar").unwrap();
+        assert_eq!(names(session.current()), ["Goblin 2"]);
+    }
+
+    #[test]
+    fn transfer_needs_another_encounter() {
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 18);
+
+        assert!(session.move_creature(0, Session::DEFAULT_NAME).is_err());
+        assert!(session.move_creature(0, "Attic").is_err());
+        assert_eq!(names(session.current()), ["Aria"]);
+    }
+
+    #[test]
+    fn encounter_names_are_unique_and_one_always_remains() {
+        let mut session = Session::new();
+        session.add(String::from("Cellar")).unwrap();
+        assert!(session.add(String::from("cellar")).is_err());
+        assert!(session.add(String::from("  ")).is_err());
+
+        session.switch("Cellar").unwrap();
+        session.remove("Cellar").unwrap();
+        assert_eq!(session.active_name(), Session::DEFAULT_NAME);
+        assert!(session.remove(Session::DEFAULT_NAME).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, io::{self, Write}, path::{Path, PathBuf}};
+
+const APP_DIR: &str = "initiative_tracker";
+
+// A file in the tracker's folder under the user's data directory
+pub fn data_file(file_name: &str) -> Result<PathBuf, String> {
+    let data_dir = dirs::data_dir().ok_or("Could not find the user's data directory.")?;
+    Ok(data_dir.join(APP_DIR).join(file_name))
+}
+
+// A missing file isn't an error, it just means nothing has been saved yet
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(format!("Could not read {}: {e}", path.display()))
+    };
+
+    serde_json::from_str(&contents).map_err(|e| format!("Could not parse {}: {e}", path.display()))
+}
+
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    write_atomic(path, &contents)
+}
+
+/*
+    Writes to a temporary file next to the real one and renames it into
+    place, so a crash partway through leaves the old file intact rather
+    than half written.
+ */
+pub fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    let mut temp_name = path.file_name().ok_or_else(|| format!("{} is not a file path.", path.display()))?.to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let write = || -> io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    };
+
+    write().map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Could not write {}: {e}", path.display())
+    })
+}
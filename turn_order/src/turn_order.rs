@@ -1,69 +1,824 @@
 use core::fmt;
+use crate::encounter::PreparedEncounter;
+use crate::roster::{CreatureTemplate, Roster};
+use serde::{Deserialize, Serialize};
 
+pub mod convert;
 pub mod creature;
-use creature::status_effect;
+pub mod creature_group;
+pub mod dice;
+pub mod entry;
+pub mod initiative;
+pub mod render;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod summary;
+use creature::{damage_modifiers::{DamageModifier, DamageModifiers}, kind::CreatureKind, recurring_effect::RecurringEffect, status_effect, Creature};
+use creature_group::CreatureGroup;
+use entry::{EventEntry, Target, TurnEntry};
+use initiative::InitiativeSystem;
+use summary::CombatSummary;
+#[cfg(feature = "scripting")]
+use scripting::{Hook, ScriptCommand, ScriptHost};
+#[cfg(feature = "scripting")]
+use std::path::Path;
 
+#[derive(Serialize, Deserialize)]
 pub struct TurnOrder {
-    creatures: Vec<creature::Creature>,
-    count: usize,
+    entries: Vec<TurnEntry>,
     // This is 0 based but will be displayed as 1 based
-    current_turn: usize
+    current_turn: usize,
+    // 1 based, increases each time the order wraps back to the top
+    round: usize,
+    // 1 based, for initiative systems that split rounds into passes
+    pass: usize,
+    combat_started: bool,
+    // Roll death saves automatically instead of prompting for them
+    auto_death_saves: bool,
+    // Collected while combat is running, reset when it starts
+    summary: CombatSummary,
+    // Saved with its state, such as the card deck, so resuming carries on with the same system
+    #[serde(with = "initiative::saved_system", default = "initiative::default_system")]
+    initiative_system: Box<dyn InitiativeSystem>,
+    // Scripts are loaded separately rather than saved with the order
+    #[cfg(feature = "scripting")]
+    #[serde(skip)]
+    scripts: ScriptHost,
+    // Script output from hooks that have no update list to add it to
+    #[cfg(feature = "scripting")]
+    #[serde(skip)]
+    script_messages: Vec<String>
 }
 
 impl TurnOrder {
 
     pub fn new() -> TurnOrder {
         TurnOrder {
-            creatures: Vec::new(),
-            count: 0,
-            current_turn: 0
+            entries: Vec::new(),
+            current_turn: 0,
+            round: 1,
+            pass: 1,
+            combat_started: false,
+            auto_death_saves: false,
+            summary: CombatSummary::new(),
+            initiative_system: initiative::default_system(),
+            #[cfg(feature = "scripting")]
+            scripts: ScriptHost::new(),
+            #[cfg(feature = "scripting")]
+            script_messages: Vec::new()
         }
     }
 
+    /*
+        Builds a turn order from every creature in the roster. The
+        initiative closure is called once per template, either rolling
+        with CreatureTemplate::roll_initiative or asking the user.
+     */
+    pub fn from_roster(roster: &Roster, mut initiative: impl FnMut(&CreatureTemplate) -> isize) -> TurnOrder {
+        let mut turn_order = TurnOrder::new();
+        for template in roster.templates() {
+            turn_order.add_creature_from_template(template, initiative(template));
+        }
+
+        turn_order
+    }
+
+    pub fn add_creature_from_template(&mut self, template: &CreatureTemplate, initiative: isize) {
+        self.add_group_from_template(template, initiative, 1);
+    }
+
+    // More than one creature are added as a group, such as "Goblin 1" to "Goblin 4"
+    pub fn add_group_from_template(&mut self, template: &CreatureTemplate, initiative: isize, count: usize) {
+        self.entries.push(template_entry(template, initiative, count));
+        self.reorder_creatures();
+    }
+
+    /*
+        Adds a prepared encounter's creatures to this turn order with
+        rolled initiative, so it can be loaded on its own or on top of
+        the party from the roster.
+     */
+    pub fn load_encounter(&mut self, encounter: &PreparedEncounter) {
+        for encounter_creature in encounter.creatures() {
+            let template = encounter_creature.template();
+            let mut entry = template_entry(template, template.roll_initiative(), encounter_creature.count());
+
+            for creature in entry.creatures_mut() {
+                creature.set_hidden(encounter_creature.hidden());
+                for effect_name in encounter_creature.starting_effects() {
+                    creature.add_status_effect(effect_name.clone());
+                }
+            }
+
+            self.entries.push(entry);
+        }
+
+        self.reorder_creatures();
+    }
+
     pub fn add_creature(&mut self, name: String, initiative: isize) {
-        self.creatures.push( creature::Creature::new(name, initiative));
+        self.entries.push(TurnEntry::Creature(Creature::new(name, initiative)));
+        self.reorder_creatures();
+    }
+
+    // Hidden creatures take turns as normal but are left out of player-facing views
+    pub fn add_creature_hidden(&mut self, name: String, initiative: isize) {
+        let mut creature = Creature::new(name, initiative);
+        creature.set_hidden(true);
+
+        self.entries.push(TurnEntry::Creature(creature));
         self.reorder_creatures();
     }
 
+    // Several creatures sharing one initiative, named "{name} 1" to "{name} {count}"
+    pub fn add_group(&mut self, name: String, initiative: isize, count: usize) {
+        self.entries.push(TurnEntry::Group(CreatureGroup::new(name, initiative, count)));
+        self.reorder_creatures();
+    }
+
+    // Non-creature entries that take a turn at a fixed initiative, losing ties
+    pub fn add_event(&mut self, name: String, initiative: isize) {
+        self.entries.push(TurnEntry::Event(EventEntry::new(name, initiative)));
+        self.reorder_creatures();
+    }
+
+    pub fn add_lair_action(&mut self) {
+        self.add_event(String::from("Lair Action"), entry::LAIR_ACTION_INITIATIVE);
+    }
+
+    /*
+        Remove the creature or event using 0-based indexing. Targeting
+        a group member removes only that member, and the group once
+        it has no members left.
+     */
+    pub fn remove_creature(&mut self, target: impl Into<Target>) -> Result<(), &'static str> {
+        let target = target.into();
+        self.creature_num_valid(target.index())?;
+
+        if let Some(member_num) = target.member_num() {
+            let group = self.entries[target.index()].as_group_mut().ok_or("That entry is not a group.")?;
+            let member = group.remove_member(member_num)?;
+            let members_left = !group.members().is_empty();
+
+            self.record_defeated(vec![&member]);
+            if members_left {
+                return Ok(());
+            }
+        }
+
+        let removed = self.remove_entry(target.index());
+        self.record_defeated(removed.creatures());
+        Ok(())
+    }
+
     /*
-        TODO: Add error handling
-        Remove the creature using 0-based indexing
+        Takes a creature or group out without counting it as defeated,
+        so it can be put into another turn order with its HP and effects
+        as they are. Targeting a group member takes only that member.
      */
-    pub fn remove_creature(&mut self, creature_index: usize) {
-        self.creatures.remove(creature_index);
+    pub fn take_creature(&mut self, target: impl Into<Target>) -> Result<TurnEntry, &'static str> {
+        let target = target.into();
+        self.creature_num_valid(target.index())?;
+
+        if let Some(member_num) = target.member_num() {
+            let group = self.entries[target.index()].as_group_mut().ok_or("That entry is not a group.")?;
+            let member = group.remove_member(member_num)?;
+            if group.members().is_empty() {
+                self.remove_entry(target.index());
+            }
+            return Ok(TurnEntry::Creature(member));
+        }
+
+        if self.entries[target.index()].is_event() {
+            return Err("Only creatures can be moved to another turn order.");
+        }
+
+        Ok(self.remove_entry(target.index()))
+    }
+
+    // Sorted in by its own initiative, for creatures taken from another turn order
+    pub fn insert_creature(&mut self, entry: TurnEntry) {
+        self.entries.push(entry);
         self.reorder_creatures();
     }
 
-    pub fn creatures(&self) -> &Vec<creature::Creature> {
-        &self.creatures
+    /*
+        Manually move a creature to a new position (0-based), overriding
+        the initiative sort. The creature takes on the initiative of its
+        new neighbour so later sorts keep it in place, and is flagged as
+        manually placed. The current turn stays with the same creature.
+     */
+    pub fn move_creature(&mut self, from: usize, to: usize) -> Result<(), &'static str> {
+        self.creature_num_valid(from)?;
+        self.creature_num_valid(to)?;
+
+        if from == to {
+            return Ok(());
+        }
+
+        self.move_entry(from, to);
+
+        // Sorting is stable, so tying with the creature above (or below, if moved to the top) holds the position
+        let neighbour = if to == 0 { 1 } else { to - 1 };
+        let initiative = self.entries[neighbour].initiative();
+        self.entries[to].place_manually(initiative);
+
+        Ok(())
     }
 
-    pub fn creature_num_valid(&self, index: usize) -> Result<(), &'static str> {
-        if index >= self.creatures.len() {
-            return Err("Creature index out of range.");
+    pub fn entries(&self) -> &Vec<TurnEntry> {
+        &self.entries
+    }
+
+    // Iterates every creature including group members, skipping events
+    pub fn creatures(&self) -> impl Iterator<Item = &Creature> {
+        self.entries.iter().flat_map(|entry| entry.creatures())
+    }
+
+    pub fn current_turn(&self) -> usize {
+        self.current_turn
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn pass(&self) -> usize {
+        self.pass
+    }
+
+    // "Round 2", or "Round 2, Pass 3" for systems with initiative passes
+    pub fn round_display(&self) -> String {
+        match self.initiative_system.has_passes() {
+            true => format!("Round {}, Pass {}", self.round, self.pass),
+            false => format!("Round {}", self.round)
+        }
+    }
+
+    pub fn combat_started(&self) -> bool {
+        self.combat_started
+    }
+
+    /*
+        Surprised creatures can't act during round 1. Meant to be set
+        before start_combat, surprise clears when round 1 ends.
+     */
+    pub fn set_surprised(&mut self, target: impl Into<Target>, surprised: bool) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_surprised(surprised);
         }
 
         Ok(())
     }
 
-    // creature_num is 0 based indexing
-    pub fn add_status_effect(&mut self, creature_num: usize, effect_name: String) -> Result<(), &'static str> {
-        match self.creatures.get_mut(creature_num) {
-            Some(creature) => {
-                creature.add_status_effect(effect_name);
+    // Legendary action points refresh at the start of the creature's turn
+    pub fn set_legendary_actions(&mut self, target: impl Into<Target>, actions: usize) -> Result<(), &'static str> {
+        self.creature_mut(target.into())?.set_legendary_actions(actions);
+        Ok(())
+    }
+
+    /*
+        Legendary actions are taken at the end of another creature's
+        turn. If Ok, returns the update to announce.
+     */
+    pub fn spend_legendary_action(&mut self, target: impl Into<Target>) -> Result<String, &'static str> {
+        let target = target.into();
+        if target.index() == self.current_turn {
+            return Err("Legendary actions can only be used on another creature's turn.");
+        }
+
+        let creature = self.creature_mut(target)?;
+        let (remaining, max) = creature.spend_legendary_action()?;
+        let name = creature.name();
+
+        Ok(format!("{name} uses a legendary action ({remaining}/{max} remaining)."))
+    }
+
+    pub fn auto_death_saves(&self) -> bool {
+        self.auto_death_saves
+    }
+
+    pub fn set_auto_death_saves(&mut self, auto_death_saves: bool) {
+        self.auto_death_saves = auto_death_saves;
+    }
+
+    pub fn set_uses_death_saves(&mut self, target: impl Into<Target>, uses_death_saves: bool) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_uses_death_saves(uses_death_saves);
+        }
+
+        Ok(())
+    }
+
+    // roll is the natural d20 result, if Ok returns the updates to announce
+    pub fn record_death_save(&mut self, target: impl Into<Target>, roll: usize) -> Result<Vec<String>, &'static str> {
+        if !(1..=20).contains(&roll) {
+            return Err("Death save rolls must be between 1 and 20.");
+        }
+
+        self.creature_mut(target.into())?.record_death_save(roll)
+    }
+
+    // If Ok, returns the update to announce
+    pub fn spend_reaction(&mut self, target: impl Into<Target>) -> Result<String, &'static str> {
+        let creature = self.creature_mut(target.into())?;
+        creature.action_economy_mut().spend_reaction()?;
+
+        let name = creature.name();
+        Ok(format!("{name} uses its reaction."))
+    }
+
+    pub fn spend_bonus_action(&mut self, target: impl Into<Target>) -> Result<String, &'static str> {
+        let creature = self.creature_mut(target.into())?;
+        creature.action_economy_mut().spend_bonus_action()?;
+
+        let name = creature.name();
+        Ok(format!("{name} uses its bonus action."))
+    }
+
+    // Movement is tracked once a speed is set, a speed of 0 stops tracking it
+    pub fn set_speed(&mut self, target: impl Into<Target>, speed: usize) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.action_economy_mut().set_speed(speed);
+        }
+
+        Ok(())
+    }
+
+    pub fn spend_movement(&mut self, target: impl Into<Target>, distance: usize) -> Result<String, &'static str> {
+        let creature = self.creature_mut(target.into())?;
+        let remaining = creature.action_economy_mut().spend_movement(distance)?;
+
+        let name = creature.name();
+        Ok(format!("{name} moves {distance} ft ({remaining} ft remaining)."))
+    }
+
+    /*
+        Begins round 1 at the top of the order. If Ok, returns the
+        updates for the start of combat and the first creature's turn.
+     */
+    pub fn start_combat(&mut self) -> Result<Vec<String>, String> {
+        if self.entries.is_empty() {
+            return Err(String::from("Cannot start combat without any creatures."));
+        }
+
+        self.round = 1;
+        self.pass = 1;
+        self.combat_started = true;
+        let mut round_updates = self.initiative_system.begin_round(&mut self.entries, self.round);
+        self.reorder_creatures();
+        self.current_turn = self.first_acting(1, 0).unwrap_or(0);
+
+        self.summary = CombatSummary::new();
+        for creature in self.entries.iter().flat_map(|entry| entry.creatures()) {
+            self.summary.creature_mut(creature.id(), creature.name(), creature.kind());
+        }
+
+        let mut all_updates = vec![String::from("Combat has started! Round 1 begins.")];
+        all_updates.append(&mut round_updates);
+        #[cfg(feature = "scripting")]
+        all_updates.append(&mut self.run_hook(Hook::RoundStart(1)));
+        self.begin_current_turn(&mut all_updates)?;
+
+        Ok(all_updates)
+    }
+
+    pub fn set_creature_hidden(&mut self, target: impl Into<Target>, hidden: bool) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_hidden(hidden);
+        }
+
+        Ok(())
+    }
+
+    /*
+        Reveals hidden creatures to the players. If Ok, returns
+        the update to announce.
+     */
+    pub fn reveal_creature(&mut self, target: impl Into<Target>) -> Result<String, &'static str> {
+        let target = target.into();
+        let mut revealed: Vec<String> = Vec::new();
+
+        for creature in self.targeted_creatures_mut(target)? {
+            if creature.hidden() {
+                creature.set_hidden(false);
+                revealed.push(creature.name().to_owned());
+            }
+        }
+
+        if revealed.is_empty() {
+            return Err("Creature is not hidden.");
+        }
+
+        // Announce a fully revealed group by its group name
+        let name = match (target.member_num(), &self.entries[target.index()]) {
+            (None, TurnEntry::Group(group)) => group.name().to_owned(),
+            _ => revealed.join(", ")
+        };
+        Ok(format!("{name} has been revealed!"))
+    }
+
+    pub fn initiative_system(&self) -> &dyn InitiativeSystem {
+        self.initiative_system.as_ref()
+    }
+
+    // Existing entries are sorted by the new system straight away
+    pub fn set_initiative_system(&mut self, initiative_system: Box<dyn InitiativeSystem>) {
+        self.initiative_system = initiative_system;
+        self.reorder_creatures();
+    }
+
+    pub fn set_initiative_modifier(&mut self, target: impl Into<Target>, modifier: isize) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_initiative_modifier(modifier);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_extra_initiative_draws(&mut self, target: impl Into<Target>, extra_draws: usize) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_extra_initiative_draws(extra_draws);
+        }
+
+        Ok(())
+    }
+
+    // Asks the initiative system for a new card or roll for the entry (0 based)
+    pub fn redraw_initiative(&mut self, index: usize) -> Result<String, &'static str> {
+        let entry = self.entries.get_mut(index).ok_or("Index out of bounds")?;
+        let update = self.initiative_system.redraw(entry)?;
+        self.reorder_creatures();
+
+        Ok(update)
+    }
+
+    /*
+        For systems where the current creature picks who goes next. The
+        chosen entry (0 based) must not have acted yet this round, unless
+        everyone has, in which case it goes first in the next round. The
+        last creature to act can pick itself to start the next round.
+     */
+    pub fn hand_off(&mut self, to: usize) -> Result<Vec<String>, String> {
+        if !self.initiative_system.hands_off() {
+            return Err(format!("{} initiative doesn't hand off turns.", self.initiative_system.name()));
+        }
+        self.creature_num_valid(to)?;
+
+        let last = self.entries.len() - 1;
+        if self.current_turn != last && to <= self.current_turn {
+            return Err(String::from("That creature has already acted this round."));
+        }
+
+        // Moved once the current turn has ended, so its end of turn effects still apply to the right creature
+        self.next_turn_with(|turn_order| {
+            if turn_order.current_turn == last {
+                turn_order.move_entry(to, 0);
+                // Still the last one to act, so the round ends and the chosen creature goes first
+                turn_order.current_turn = last;
+            }
+            else {
+                turn_order.move_entry(to, turn_order.current_turn + 1);
+            }
+        })
+    }
+
+    pub fn set_group_expanded(&mut self, group_num: usize, expanded: bool) -> Result<(), &'static str> {
+        match self.entries.get_mut(group_num) {
+            Some(entry) => {
+                entry.as_group_mut().ok_or("That entry is not a group.")?.set_expanded(expanded);
                 Ok(())
             }
             None => Err("Index out of bounds")
         }
     }
 
-    pub fn add_status_effect_timed(&mut self, creature_num: usize, effect_name: String, duration: usize, clear_type: status_effect::ClearType) -> Result<(), &'static str> {
-        match self.creatures.get_mut(creature_num) {
-            Some(creature) => {
-                creature.add_status_effect_timed(effect_name, duration, clear_type);
-                Ok(())
+    // Starts tracking HP at full health
+    pub fn set_max_hit_points(&mut self, target: impl Into<Target>, max: usize) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_max_hit_points(max);
+        }
+
+        Ok(())
+    }
+
+    // If Ok, returns the updates to announce
+    pub fn damage(&mut self, target: impl Into<Target>, amount: usize) -> Result<Vec<String>, &'static str> {
+        self.apply_damage(target.into(), amount, None, false)
+    }
+
+    // Damage of a type such as "fire", scaled by each creature's resistances
+    pub fn damage_typed(&mut self, target: impl Into<Target>, amount: usize, damage_type: &str) -> Result<Vec<String>, &'static str> {
+        self.apply_damage(target.into(), amount, Some(damage_type), false)
+    }
+
+    // Damage from a critical hit counts as two failed death saves on a creature already down
+    pub fn damage_critical(&mut self, target: impl Into<Target>, amount: usize, damage_type: Option<&str>) -> Result<Vec<String>, &'static str> {
+        self.apply_damage(target.into(), amount, damage_type, true)
+    }
+
+    pub fn add_damage_modifier(&mut self, target: impl Into<Target>, modifier: DamageModifier, damage_type: String) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.damage_modifiers_mut().add(modifier, damage_type.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_damage_modifiers(&mut self, target: impl Into<Target>, damage_modifiers: DamageModifiers) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_damage_modifiers(damage_modifiers.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn set_condition_immunities(&mut self, target: impl Into<Target>, condition_immunities: Vec<String>) -> Result<(), &'static str> {
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_condition_immunities(condition_immunities.clone());
+        }
+
+        Ok(())
+    }
+
+    fn apply_damage(&mut self, target: Target, amount: usize, damage_type: Option<&str>, critical: bool) -> Result<Vec<String>, &'static str> {
+        let was_over = self.combat_over();
+
+        let mut updates: Vec<String> = Vec::new();
+        let mut damaged: Vec<(SummaryKey, usize)> = Vec::new();
+        let creatures = self.targeted_creatures_mut(target)?;
+        if let Some(e) = creatures.iter().find_map(|creature| creature.can_take_damage().err()) {
+            return Err(e);
+        }
+        for creature in creatures {
+            let current_hit_points = |creature: &Creature| creature.hit_points().map(|hit_points| hit_points.current()).unwrap_or(0);
+            let before = current_hit_points(creature);
+            let mut creature_updates = match damage_type {
+                Some(damage_type) => creature.take_typed_damage(amount, damage_type, critical)?,
+                None => creature.take_damage(amount, critical)?
+            };
+            updates.append(&mut creature_updates);
+            damaged.push((summary_key(creature), before - current_hit_points(creature)));
+        }
+
+        #[cfg(feature = "scripting")]
+        let damage_hooks: Vec<Hook> = damaged.iter()
+            .filter(|(_, taken)| *taken > 0)
+            .map(|((_, name, _), taken)| Hook::DamageTaken(name.clone(), *taken))
+            .collect();
+
+        if self.combat_started {
+            self.record_damage(damaged);
+        }
+
+        #[cfg(feature = "scripting")]
+        for hook in damage_hooks {
+            updates.append(&mut self.run_hook(hook));
+        }
+
+        if let Some(reason) = self.combat_over_reason().filter(|_| !was_over) {
+            updates.push(String::from(reason));
+        }
+
+        Ok(updates)
+    }
+
+    // Combat is over once it has started and every enemy in it is defeated
+    pub fn combat_over(&self) -> bool {
+        self.combat_over_reason().is_some()
+    }
+
+    // Only creatures tagged as enemies or player characters decide when the fight is over
+    pub fn combat_over_reason(&self) -> Option<&'static str> {
+        if !self.combat_started {
+            return None;
+        }
+
+        if self.all_enemies_defeated() {
+            Some("All enemies have been defeated!")
+        }
+        else if self.all_defeated(CreatureKind::PlayerCharacter) {
+            Some("The party has been defeated!")
+        }
+        else {
+            None
+        }
+    }
+
+    // The summary so far, with creatures still in the order marked defeated if they're down
+    pub fn combat_summary(&self) -> CombatSummary {
+        let mut summary = self.summary.clone();
+        summary.set_rounds(self.round);
+
+        for creature in self.creatures() {
+            summary.creature_mut(creature.id(), creature.name(), creature.kind()).set_defeated(creature.is_defeated());
+        }
+
+        summary
+    }
+
+    // Ends combat manually or once it's over, returning what happened
+    pub fn end_combat(&mut self) -> Result<CombatSummary, &'static str> {
+        if !self.combat_started {
+            return Err("Combat has not started.");
+        }
+
+        let summary = self.combat_summary();
+        self.combat_started = false;
+
+        Ok(summary)
+    }
+
+    // True when every enemy is at 0 HP or has been removed, and there were enemies to begin with
+    pub fn all_enemies_defeated(&self) -> bool {
+        self.all_defeated(CreatureKind::Enemy)
+    }
+
+    fn all_defeated(&self, kind: CreatureKind) -> bool {
+        self.summary.has_kind(kind) && self.creatures()
+            .filter(|creature| creature.kind() == kind)
+            .all(|creature| creature.is_defeated())
+    }
+
+    pub fn set_kind(&mut self, target: impl Into<Target>, kind: CreatureKind) -> Result<(), &'static str> {
+        let mut tagged: Vec<SummaryKey> = Vec::new();
+        for creature in self.targeted_creatures_mut(target.into())? {
+            creature.set_kind(kind);
+            tagged.push(summary_key(creature));
+        }
+
+        // Creatures tagged partway through the fight count towards their side from then on
+        if self.combat_started {
+            for (id, name, kind) in tagged {
+                self.summary.creature_mut(id, &name, kind);
+            }
+        }
+
+        self.reorder_creatures();
+        Ok(())
+    }
+
+    pub fn heal(&mut self, target: impl Into<Target>, amount: usize) -> Result<Vec<String>, &'static str> {
+        let creatures = self.targeted_creatures_mut(target.into())?;
+        if let Some(e) = creatures.iter().find_map(|creature| creature.can_heal().err()) {
+            return Err(e);
+        }
+
+        let mut updates: Vec<String> = Vec::new();
+        for creature in creatures {
+            updates.append(&mut creature.heal(amount)?);
+        }
+
+        Ok(updates)
+    }
+
+    pub fn player_display(&self) -> PlayerDisplay<'_> {
+        PlayerDisplay { turn_order: self }
+    }
+
+    pub fn set_status_effect_hidden(&mut self, target: impl Into<Target>, effect_num: usize, hidden: bool) -> Result<(), &'static str> {
+        self.creature_mut(target.into())?.set_status_effect_hidden(effect_num, hidden)
+    }
+
+    pub fn creature_num_valid(&self, index: usize) -> Result<(), &'static str> {
+        if index >= self.entries.len() {
+            return Err("Creature index out of range.");
+        }
+
+        Ok(())
+    }
+
+    // Target is 0 based indexing, targeting a whole group applies to every member
+    pub fn add_status_effect(&mut self, target: impl Into<Target>, effect_name: String) -> Result<(), &'static str> {
+        let target = target.into();
+        self.check_condition_immunity(target, &effect_name)?;
+        for creature in self.targeted_creatures_mut(target)? {
+            creature.add_status_effect(effect_name.clone());
+        }
+
+        self.record_effect_applied(target, &effect_name);
+        Ok(())
+    }
+
+    /*
+        Applies the effect to every creature of that kind, skipping any
+        immune to it. Returns how many were affected.
+     */
+    pub fn add_status_effect_to_kind(&mut self, kind: CreatureKind, effect_name: String) -> usize {
+        let creatures = self.entries.iter_mut()
+            .flat_map(|entry| entry.creatures_mut())
+            .filter(|creature| creature.kind() == kind && !creature.is_immune_to(&effect_name));
+
+        let mut affected: Vec<String> = Vec::new();
+        for creature in creatures {
+            creature.add_status_effect(effect_name.clone());
+            if self.combat_started {
+                self.summary.creature_mut(creature.id(), creature.name(), kind).add_effect_applied(effect_name.clone());
+            }
+            affected.push(creature.name().to_string());
+        }
+
+        let count = affected.len();
+        #[cfg(feature = "scripting")]
+        for name in affected {
+            let mut updates = self.run_hook(Hook::EffectAdded(name, effect_name.clone()));
+            self.script_messages.append(&mut updates);
+        }
+
+        count
+    }
+
+    pub fn add_status_effect_hidden(&mut self, target: impl Into<Target>, effect_name: String) -> Result<(), &'static str> {
+        let target = target.into();
+        self.check_condition_immunity(target, &effect_name)?;
+        for creature in self.targeted_creatures_mut(target)? {
+            creature.add_status_effect_hidden(effect_name.clone());
+        }
+
+        self.record_effect_applied(target, &effect_name);
+        Ok(())
+    }
+
+    pub fn add_status_effect_timed(&mut self, target: impl Into<Target>, effect_name: String, duration: usize, clear_type: status_effect::ClearType) -> Result<(), &'static str> {
+        let target = target.into();
+        self.check_condition_immunity(target, &effect_name)?;
+        for creature in self.targeted_creatures_mut(target)? {
+            creature.add_status_effect_timed(effect_name.clone(), duration, clear_type);
+        }
+
+        self.record_effect_applied(target, &effect_name);
+        Ok(())
+    }
+
+    pub fn add_status_effect_valued(&mut self, target: impl Into<Target>, effect_name: String, value: usize, rule: status_effect::ValueRule) -> Result<(), &'static str> {
+        if value == 0 {
+            return Err("Condition value must be at least 1.");
+        }
+
+        let target = target.into();
+        self.check_condition_immunity(target, &effect_name)?;
+        for creature in self.targeted_creatures_mut(target)? {
+            creature.add_status_effect_valued(effect_name.clone(), value, rule);
+        }
+
+        self.record_effect_applied(target, &effect_name);
+        Ok(())
+    }
+
+    // Each targeted creature rolls the effect's damage or healing separately
+    pub fn add_status_effect_recurring(&mut self, target: impl Into<Target>, effect_name: String, duration: Option<usize>, recurring: RecurringEffect) -> Result<(), &'static str> {
+        let target = target.into();
+        self.check_condition_immunity(target, &effect_name)?;
+        for creature in self.targeted_creatures_mut(target)? {
+            creature.add_status_effect_recurring(effect_name.clone(), duration, recurring.clone());
+        }
+
+        self.record_effect_applied(target, &effect_name);
+        Ok(())
+    }
+
+    // Raises or lowers a valued condition by hand, ending it once it reaches 0
+    pub fn adjust_condition_value(&mut self, target: impl Into<Target>, effect_num: usize, change: isize) -> Result<String, &'static str> {
+        let before = self.effect_snapshot();
+        let update = self.creature_mut(target.into())?.adjust_condition_value(effect_num, change)?;
+        self.record_effects_expired(before);
+
+        Ok(update)
+    }
+
+    // Effects are rejected for a whole group if any targeted member is immune
+    fn check_condition_immunity(&mut self, target: Target, effect_name: &str) -> Result<(), &'static str> {
+        if self.targeted_creatures_mut(target)?.iter().any(|creature| creature.is_immune_to(effect_name)) {
+            return Err("Creature is immune to that condition.");
+        }
+
+        Ok(())
+    }
+
+    // For commands that only make sense on one creature, such as a single group member
+    fn creature_mut(&mut self, target: Target) -> Result<&mut Creature, &'static str> {
+        let mut creatures = self.targeted_creatures_mut(target)?;
+        if creatures.len() != 1 {
+            return Err("Select a single group member.");
+        }
+
+        Ok(creatures.remove(0))
+    }
+
+    fn targeted_creatures_mut(&mut self, target: Target) -> Result<Vec<&mut Creature>, &'static str> {
+        let entry = self.entries.get_mut(target.index()).ok_or("Index out of bounds")?;
+
+        match (entry, target.member_num()) {
+            (TurnEntry::Event(_), _) => Err("That entry is an event, not a creature."),
+            (TurnEntry::Creature(creature), None) => Ok(vec![creature]),
+            (TurnEntry::Creature(_), Some(_)) => Err("That entry is not a group."),
+            (TurnEntry::Group(group), None) => Ok(group.members_mut().iter_mut().collect()),
+            (TurnEntry::Group(group), Some(member_num)) => {
+                match group.members_mut().get_mut(member_num) {
+                    Some(member) => Ok(vec![member]),
+                    None => Err("Group member index out of range.")
+                }
             }
-            None => Err("Index out of bounds")
         }
     }
 
@@ -72,9 +827,21 @@ impl TurnOrder {
         represent creature updates.
      */
     pub fn next_turn(&mut self) -> Result<Vec<String>, String> {
+        self.next_turn_with(|_| ())
+    }
+
+    // Runs before_advance after the current turn has ended and before the next one is picked
+    fn next_turn_with(&mut self, before_advance: impl FnOnce(&mut TurnOrder)) -> Result<Vec<String>, String> {
         let mut all_updates: Vec<String> = Vec::new();
+        let effects_before = self.effect_snapshot();
+
+        #[cfg(feature = "scripting")]
+        for name in self.entries.get(self.current_turn).map(entry_creature_names).unwrap_or_default() {
+            all_updates.append(&mut self.run_hook(Hook::TurnEnd(name)));
+        }
+
         // Call end turn on current creature and begin turn on the next
-        match self.creatures.get_mut(self.current_turn) {
+        match self.entries.get_mut(self.current_turn) {
             Some(creature) => {
                 match creature.end_turn() {
                     creature::CreatureUpdate::Updates(ref mut creature_updates) => {
@@ -90,59 +857,453 @@ impl TurnOrder {
             }
         }
 
-        self.increase_turn_counter();
+        before_advance(self);
+        if self.advance_turn(&mut all_updates) {
+            self.end_round(&mut all_updates);
+        }
 
-        match self.creatures.get_mut(self.current_turn) {
-            Some(creature) => {
-                match creature.begin_turn() {
+        self.begin_current_turn(&mut all_updates)?;
+        self.record_effects_expired(effects_before);
+
+        Ok(all_updates)
+    }
+
+    fn begin_current_turn(&mut self, all_updates: &mut Vec<String>) -> Result<(), String> {
+        match self.entries.get_mut(self.current_turn) {
+            Some(entry) => {
+                match entry.begin_turn() {
                     creature::CreatureUpdate::Updates(ref mut creature_updates) => {
                         all_updates.append(creature_updates);
                     }
                     creature::CreatureUpdate::NoUpdate => ()
                 }
+
+                #[cfg(feature = "scripting")]
+                let names = entry_creature_names(entry);
+
+                for creature in entry.creatures_mut() {
+                    if self.combat_started {
+                        self.summary.creature_mut(creature.id(), creature.name(), creature.kind()).add_turn();
+                    }
+
+                    if creature.surprised() {
+                        let name = creature.name();
+                        all_updates.push(format!("{name} is surprised and cannot act this turn."));
+                    }
+
+                    if creature.is_dying() {
+                        if self.auto_death_saves {
+                            let mut save_updates = creature.record_death_save(dice::roll_d20())?;
+                            all_updates.append(&mut save_updates);
+                        }
+                        else {
+                            let name = creature.name();
+                            all_updates.push(format!("{name} is dying and must make a death saving throw."));
+                        }
+                    }
+                }
+
+                #[cfg(feature = "scripting")]
+                for name in names {
+                    all_updates.append(&mut self.run_hook(Hook::TurnStart(name)));
+                }
+
+                Ok(())
             }
             None => {
                 let index = self.current_turn;
-                return Err(format!("Error advancing turn: no creature found at index {index}"));
+                Err(format!("Error advancing turn: no creature found at index {index}"))
+            }
+        }
+    }
+
+    // Surprise only lasts for the first round
+    fn end_round(&mut self, all_updates: &mut Vec<String>) {
+        if self.round == 1 {
+            for creature in self.entries.iter_mut().flat_map(|entry| entry.creatures_mut()) {
+                if creature.surprised() {
+                    creature.set_surprised(false);
+                    let name = creature.name();
+                    all_updates.push(format!("{name} is no longer surprised."));
+                }
+            }
+        }
+
+        self.round += 1;
+        self.pass = 1;
+        let round = self.round;
+        all_updates.push(format!("Round {round} begins."));
+
+        all_updates.append(&mut self.initiative_system.begin_round(&mut self.entries, round));
+        self.reorder_creatures();
+        self.current_turn = self.first_acting(1, 0).unwrap_or(0);
+
+        #[cfg(feature = "scripting")]
+        all_updates.append(&mut self.run_hook(Hook::RoundStart(round)));
+    }
+
+    fn record_effect_applied(&mut self, target: Target, effect_name: &str) {
+        #[cfg(feature = "scripting")]
+        if let Some(entry) = self.entries.get(target.index()) {
+            let names = match (entry.as_group(), target.member_num()) {
+                (Some(group), Some(member_num)) => group.members().get(member_num).map(|member| vec![member.name().to_string()]).unwrap_or_default(),
+                _ => entry_creature_names(entry)
+            };
+            for name in names {
+                let mut updates = self.run_hook(Hook::EffectAdded(name, effect_name.to_string()));
+                self.script_messages.append(&mut updates);
             }
         }
 
+        if !self.combat_started {
+            return;
+        }
+
+        let Ok(creatures) = self.targeted_creatures_mut(target) else {
+            return;
+        };
+        let creatures: Vec<SummaryKey> = creatures.iter().map(|creature| summary_key(creature)).collect();
+
+        for (id, name, kind) in creatures {
+            self.summary.creature_mut(id, &name, kind).add_effect_applied(effect_name.to_string());
+        }
+    }
+
+    // Damage taken by each creature, credited as dealt by whoever's turn it is
+    fn record_damage(&mut self, damaged: Vec<(SummaryKey, usize)>) {
+        let mut total = 0;
+        for ((id, name, kind), taken) in damaged {
+            self.summary.creature_mut(id, &name, kind).add_damage_taken(taken);
+            total += taken;
+        }
+
+        if let Some(entry) = self.entries.get(self.current_turn) {
+            let kind = entry.kind().unwrap_or(CreatureKind::Environment);
+            self.summary.creature_mut(entry.id(), entry.name(), kind).add_damage_dealt(total);
+        }
+    }
+
+    // Removed creatures keep their last known state in the summary
+    fn record_defeated(&mut self, creatures: Vec<&Creature>) {
+        if !self.combat_started {
+            return;
+        }
+
+        for creature in creatures {
+            self.summary.creature_mut(creature.id(), creature.name(), creature.kind()).set_defeated(creature.is_defeated());
+        }
+    }
+
+    // Each creature with the id and name of each of its effects
+    fn effect_snapshot(&self) -> Vec<(SummaryKey, usize, String)> {
+        self.creatures()
+            .flat_map(|creature| creature.status_effects().iter()
+                .map(|effect| (summary_key(creature), effect.id(), effect.name().to_string())))
+            .collect()
+    }
+
+    fn record_effects_expired(&mut self, before: Vec<(SummaryKey, usize, String)>) {
+        if !self.combat_started {
+            return;
+        }
 
-        return Ok(all_updates);
+        let after = self.effect_snapshot();
+        for ((creature_id, name, kind), id, effect_name) in before {
+            if !after.iter().any(|((other_creature_id, _, _), other_id, _)| *other_creature_id == creature_id && *other_id == id) {
+                self.summary.creature_mut(creature_id, &name, kind).add_effect_expired(effect_name);
+            }
+        }
     }
 
+    /*
+        Sorts with the initiative system while keeping the current turn
+        on the same creature. Remaining ties put events last and are then
+        broken by creature kind, if the system allows it, unless one of
+        the tied entries was placed by hand and is relying on the order
+        staying as it is.
+     */
     fn reorder_creatures(&mut self) {
-        self.creatures.sort_by(|a, b| b.initiative().cmp(&a.initiative()));
+        let system = self.initiative_system.as_ref();
+        let mut indexed: Vec<(usize, TurnEntry)> = self.entries.drain(..).enumerate().collect();
+        indexed.sort_by(|(_, a), (_, b)| system.compare(a, b));
+
+        let tied = |(_, a): &(usize, TurnEntry), (_, b): &(usize, TurnEntry)| system.compare(a, b).is_eq();
+        for ties in indexed.chunk_by_mut(tied) {
+            if ties.iter().any(|(_, entry)| entry.manually_placed()) {
+                continue;
+            }
+
+            let event_last = |entry: &TurnEntry| system.events_lose_ties() && entry.is_event();
+            let kind = |entry: &TurnEntry| entry.kind().filter(|_| system.kind_breaks_ties());
+            ties.sort_by_key(|(_, entry)| (event_last(entry), kind(entry)));
+        }
+
+        self.current_turn = indexed.iter()
+            .position(|(index, _)| *index == self.current_turn)
+            .unwrap_or(0);
+        self.entries = indexed.into_iter().map(|(_, entry)| entry).collect();
     }
 
-    fn increase_turn_counter(&mut self) {
-        self.current_turn += 1;
-        if self.current_turn >= self.creatures.len() {
+    // Keeps the turn on the same creature, or the one that took the removed creature's place
+    fn remove_entry(&mut self, index: usize) -> TurnEntry {
+        let removed = self.entries.remove(index);
+
+        if index < self.current_turn {
+            self.current_turn -= 1;
+        }
+        if self.current_turn >= self.entries.len() {
             self.current_turn = 0;
         }
+
+        self.reorder_creatures();
+        removed
+    }
+
+    // Moves an entry (0 based) without changing its initiative, the current turn stays with the same entry
+    fn move_entry(&mut self, from: usize, to: usize) {
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+
+        if self.current_turn == from {
+            self.current_turn = to;
+        }
+        else if from < self.current_turn && self.current_turn <= to {
+            self.current_turn -= 1;
+        }
+        else if to <= self.current_turn && self.current_turn < from {
+            self.current_turn += 1;
+        }
+    }
+
+    /*
+        Moves to the next entry acting in this pass, then the first
+        acting in the next pass. Returns true once no one is left to
+        act and the order wraps back to the top for a new round.
+     */
+    fn advance_turn(&mut self, all_updates: &mut Vec<String>) -> bool {
+        if let Some(next) = self.first_acting(self.pass, self.current_turn + 1) {
+            self.current_turn = next;
+            return false;
+        }
+
+        if let Some(next) = self.first_acting(self.pass + 1, 0) {
+            self.pass += 1;
+            self.current_turn = next;
+            all_updates.push(format!("Pass {} begins.", self.pass));
+            return false;
+        }
+
+        self.current_turn = 0;
+        true
+    }
+
+    // The first entry from the given index on that takes a turn in the pass
+    fn first_acting(&self, pass: usize, from: usize) -> Option<usize> {
+        (from..self.entries.len()).find(|index| self.initiative_system.acts_in_pass(&self.entries[*index], pass))
     }
 
     
 }
 
+#[cfg(feature = "scripting")]
+impl TurnOrder {
+    pub fn scripts(&self) -> &ScriptHost {
+        &self.scripts
+    }
+
+    pub fn scripts_mut(&mut self) -> &mut ScriptHost {
+        &mut self.scripts
+    }
+
+    // Loads a script file or folder of scripts, returning how many were loaded
+    pub fn import_scripts(&mut self, path: &Path) -> Result<usize, String> {
+        self.scripts.import(path)
+    }
+
+    // Messages and errors from hooks run outside of next_turn and the like, such as effects being added
+    pub fn take_script_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.script_messages)
+    }
+
+    // Runs the hook in every script and applies what they asked for, returning the updates
+    fn run_hook(&mut self, hook: Hook) -> Vec<String> {
+        if self.scripts.is_empty() {
+            return Vec::new();
+        }
+
+        let commands = self.scripts.run(&hook, self.round, self.entries.iter().flat_map(|entry| entry.creatures()));
+
+        let mut updates: Vec<String> = Vec::new();
+        for command in commands {
+            let result = match command {
+                ScriptCommand::Message(text) => Ok(vec![text]),
+                ScriptCommand::Damage(name, amount) => self.script_damage(&name, amount),
+                ScriptCommand::Heal(name, amount) => self.script_creature_mut(&name)
+                    .and_then(|creature| creature.heal(amount)),
+                ScriptCommand::AddEffect(name, effect_name) => self.script_creature_mut(&name)
+                    .and_then(|creature| match creature.is_immune_to(&effect_name) {
+                        true => Err("Creature is immune to that condition."),
+                        false => {
+                            creature.add_status_effect(effect_name);
+                            Ok(Vec::new())
+                        }
+                    }),
+                ScriptCommand::RemoveEffect(name, effect_name) => self.script_creature_mut(&name)
+                    .and_then(|creature| creature.remove_status_effect_named(&effect_name))
+                    .map(|update| vec![update])
+            };
+
+            match result {
+                Ok(mut command_updates) => updates.append(&mut command_updates),
+                Err(e) => updates.push(format!("Script error: {e}"))
+            }
+        }
+
+        updates
+    }
+
+    // Counted in the combat summary like any other damage, without running the hooks again
+    fn script_damage(&mut self, name: &str, amount: usize) -> Result<Vec<String>, &'static str> {
+        let was_over = self.combat_over();
+
+        let creature = self.script_creature_mut(name)?;
+        let before = creature.hit_points().map(|hit_points| hit_points.current()).unwrap_or(0);
+        let mut updates = creature.take_damage(amount, false)?;
+        let taken = before - creature.hit_points().map(|hit_points| hit_points.current()).unwrap_or(0);
+        let damaged = vec![(summary_key(creature), taken)];
+
+        if self.combat_started {
+            self.record_damage(damaged);
+        }
+        if let Some(reason) = self.combat_over_reason().filter(|_| !was_over) {
+            updates.push(String::from(reason));
+        }
+
+        Ok(updates)
+    }
+
+    fn script_creature_mut(&mut self, name: &str) -> Result<&mut Creature, &'static str> {
+        self.entries.iter_mut()
+            .flat_map(|entry| entry.creatures_mut())
+            .find(|creature| creature.name().eq_ignore_ascii_case(name))
+            .ok_or("No creature with that name.")
+    }
+}
+
+// A creature's id, name and kind, enough to find or start its record in the combat summary
+type SummaryKey = (u64, String, CreatureKind);
+
+fn summary_key(creature: &Creature) -> SummaryKey {
+    (creature.id(), creature.name().to_string(), creature.kind())
+}
+
+#[cfg(feature = "scripting")]
+fn entry_creature_names(entry: &TurnEntry) -> Vec<String> {
+    entry.creatures().iter().map(|creature| creature.name().to_string()).collect()
+}
+
+// Player characters from a template make death saves
+fn template_entry(template: &CreatureTemplate, initiative: isize, count: usize) -> TurnEntry {
+    let name = template.name().to_string();
+    let mut entry = match count {
+        0 | 1 => TurnEntry::Creature(Creature::new(name, initiative)),
+        count => TurnEntry::Group(CreatureGroup::new(name, initiative, count))
+    };
+
+    for creature in entry.creatures_mut() {
+        creature.set_kind(template.kind());
+        creature.set_initiative_modifier(template.initiative_modifier());
+        creature.set_uses_death_saves(template.kind() == CreatureKind::PlayerCharacter);
+        creature.set_condition_immunities(template.condition_immunities().clone());
+        creature.set_damage_modifiers(template.damage_modifiers().clone());
+        creature.set_passives(template.passive_perception(), template.passive_insight(), template.passive_investigation());
+        if let Some(max_hit_points) = template.max_hit_points() {
+            creature.set_max_hit_points(max_hit_points);
+        }
+    }
+
+    entry
+}
+
+impl Default for TurnOrder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl fmt::Display for TurnOrder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut turn_order_str = String::new();
 
-        let mut creature_num: usize = 1;
+        if self.combat_started {
+            turn_order_str.push_str(&format!("{}\n", self.round_display()));
+        }
+
+        for (index, creature) in self.entries.iter().enumerate() {
+            let creature_num = index + 1;
+            let initiative = self.initiative_system.initiative_display(creature);
+            let manual = if creature.manually_placed() { " [MANUAL]" } else { "" };
+            let hidden = if creature.hidden() { " [HIDDEN]" } else { "" };
+            let surprised = if creature.surprised() { " [SURPRISED]" } else { "" };
+            let kind = creature.kind().map(|kind| format!(" [{kind}]")).unwrap_or_default();
+
+            if index == self.current_turn {
+                turn_order_str.push_str(&format!("[{creature_num}] [CURRENT TURN] {initiative}{kind}{manual}{hidden}{surprised} {creature}\n"))
+            }
+
+            else {
+                turn_order_str.push_str(&format!("[{creature_num}] {initiative}{kind}{manual}{hidden}{surprised} {creature}\n"))
+            }
+
+            if let Some(group) = creature.as_group().filter(|group| group.expanded()) {
+                for (member_num, member) in (1..).zip(group.members()) {
+                    let hidden = if member.hidden() { " [HIDDEN]" } else { "" };
+                    let surprised = if member.surprised() { " [SURPRISED]" } else { "" };
+                    turn_order_str.push_str(&format!("    [{creature_num}.{member_num}]{hidden}{surprised} {member}\n"));
+                }
+            }
+        }
+
+        write!(f, "{turn_order_str}")
+    }
+}
+
+/*
+    The turn order as players should see it. Hidden creatures and
+    effects are left out, and the remaining creatures are numbered
+    without gaps so nothing hints at what's hidden.
+ */
+pub struct PlayerDisplay<'a> {
+    turn_order: &'a TurnOrder
+}
+
+impl fmt::Display for PlayerDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut turn_order_str = String::new();
+
+        if self.turn_order.combat_started {
+            turn_order_str.push_str(&format!("{}\n", self.turn_order.round_display()));
+        }
+
+        let visible = self.turn_order.entries.iter().enumerate()
+            .filter(|(_, creature)| !creature.hidden());
 
-        for creature in &self.creatures {
-            let initiative = creature.initiative();
+        for (creature_num, (index, creature)) in (1..).zip(visible) {
+            let initiative = self.turn_order.initiative_system.initiative_display(creature);
 
-            if creature_num - 1 == self.current_turn {
-                turn_order_str.push_str(&format!("[{creature_num}] [CURRENT TURN] I:{initiative} {creature}\n"))
+            if index == self.turn_order.current_turn {
+                turn_order_str.push_str(&format!("[{creature_num}] [CURRENT TURN] {initiative} {}\n", creature.public_display()))
             }
 
             else {
-                turn_order_str.push_str(&format!("[{creature_num}] I:{initiative} {creature}\n"))
+                turn_order_str.push_str(&format!("[{creature_num}] {initiative} {}\n", creature.public_display()))
             }
 
-            creature_num += 1;
+            if let Some(group) = creature.as_group().filter(|group| group.expanded()) {
+                for member in group.members().iter().filter(|member| !member.hidden()) {
+                    turn_order_str.push_str(&format!("    {}\n", member.public_display()));
+                }
+            }
         }
 
         write!(f, "{turn_order_str}")
@@ -150,3 +1311,417 @@ impl fmt::Display for TurnOrder {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(turn_order: &TurnOrder) -> Vec<&str> {
+        turn_order.entries().iter().map(|entry| entry.name()).collect()
+    }
+
+    fn popcorn_order() -> TurnOrder {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.add_creature(String::from("Cade"), 8);
+        turn_order.set_initiative_system(Box::new(initiative::Popcorn));
+        turn_order.start_combat().unwrap();
+
+        turn_order
+    }
+
+    #[test]
+    fn hand_off_needs_a_handoff_system() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.start_combat().unwrap();
+
+        assert!(turn_order.hand_off(1).is_err());
+        assert_eq!(turn_order.current_turn(), 0);
+    }
+
+    #[test]
+    fn hand_off_picks_who_goes_next() {
+        let mut turn_order = popcorn_order();
+
+        turn_order.hand_off(2).unwrap();
+        assert_eq!(names(&turn_order), ["Aria", "Cade", "Brom"]);
+        assert_eq!(turn_order.current_turn(), 1);
+
+        assert!(turn_order.hand_off(0).is_err());
+    }
+
+    #[test]
+    fn last_creature_can_hand_off_to_itself() {
+        let mut turn_order = popcorn_order();
+        turn_order.hand_off(1).unwrap();
+        turn_order.hand_off(2).unwrap();
+        assert_eq!(turn_order.current_turn(), 2);
+
+        turn_order.hand_off(2).unwrap();
+        assert_eq!(turn_order.round(), 2);
+        assert_eq!(turn_order.current_turn(), 0);
+        assert_eq!(names(&turn_order), ["Cade", "Aria", "Brom"]);
+    }
+
+    #[test]
+    fn hidden_creatures_are_left_out_for_players() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature_hidden(String::from("Assassin"), 20);
+        turn_order.add_creature(String::from("Brom"), 12);
+
+        assert_eq!(turn_order.player_display().to_string(), "[1] [CURRENT TURN] I:18 Aria\n[2] I:12 Brom\n");
+        assert!(!turn_order.to_markdown(false).contains("Assassin"));
+        assert!(!turn_order.to_html(false).contains("Assassin"));
+        assert!(turn_order.to_string().contains("Assassin"));
+    }
+
+    #[test]
+    fn revealing_announces_the_creature_once() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature_hidden(String::from("Assassin"), 20);
+
+        assert_eq!(turn_order.reveal_creature(0).unwrap(), "Assassin has been revealed!");
+        assert!(turn_order.player_display().to_string().contains("Assassin"));
+        assert!(turn_order.reveal_creature(0).is_err());
+    }
+
+    #[test]
+    fn revealing_a_hidden_group_names_the_group() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_group(String::from("Goblin"), 12, 2);
+        turn_order.set_creature_hidden(0, true).unwrap();
+
+        assert_eq!(turn_order.reveal_creature(Target::member(0, 1)).unwrap(), "Goblin 2 has been revealed!");
+        assert_eq!(turn_order.reveal_creature(0).unwrap(), "Goblin has been revealed!");
+    }
+
+    #[test]
+    fn surprise_only_lasts_the_first_round() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Goblin"), 12);
+        turn_order.set_surprised(1, true).unwrap();
+        turn_order.start_combat().unwrap();
+
+        let updates = turn_order.next_turn().unwrap();
+        assert_eq!(updates, ["Goblin is surprised and cannot act this turn."]);
+
+        let updates = turn_order.next_turn().unwrap();
+        assert!(updates.iter().any(|update| update == "Goblin is no longer surprised."), "{updates:?}");
+        assert!(turn_order.creatures().all(|creature| !creature.surprised()));
+
+        let updates = turn_order.next_turn().unwrap();
+        assert!(updates.is_empty(), "{updates:?}");
+    }
+
+    #[test]
+    fn removing_needs_a_valid_target() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_group(String::from("Goblin"), 12, 2);
+
+        assert!(turn_order.remove_creature(2).is_err());
+        assert!(turn_order.remove_creature(Target::member(0, 0)).is_err());
+        assert!(turn_order.remove_creature(Target::member(1, 2)).is_err());
+        assert_eq!(names(&turn_order), ["Aria", "Goblin"]);
+        assert_eq!(turn_order.entries()[1].as_group().unwrap().members().len(), 2);
+    }
+
+    #[test]
+    fn removing_the_last_member_removes_the_group() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_group(String::from("Goblin"), 12, 2);
+
+        turn_order.remove_creature(Target::member(0, 0)).unwrap();
+        assert_eq!(turn_order.entries()[0].as_group().unwrap().members()[0].name(), "Goblin 2");
+        turn_order.remove_creature(Target::member(0, 0)).unwrap();
+        assert!(turn_order.entries().is_empty());
+    }
+
+    fn member_hit_points(turn_order: &TurnOrder, index: usize) -> Vec<Option<usize>> {
+        turn_order.entries()[index].as_group().unwrap().members().iter()
+            .map(|member| member.hit_points().map(|hit_points| hit_points.current()))
+            .collect()
+    }
+
+    #[test]
+    fn damage_and_healing_target_one_member_or_the_whole_group() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_group(String::from("Goblin"), 12, 3);
+        turn_order.set_max_hit_points(0, 7).unwrap();
+
+        turn_order.damage(Target::member(0, 1), 4).unwrap();
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(7), Some(3), Some(7)]);
+
+        turn_order.damage(0, 2).unwrap();
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(5), Some(1), Some(5)]);
+
+        turn_order.heal(0, 1).unwrap();
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(6), Some(2), Some(6)]);
+
+        assert!(turn_order.damage(Target::member(0, 3), 2).is_err());
+        assert!(turn_order.heal(Target::member(0, 3), 2).is_err());
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(6), Some(2), Some(6)]);
+    }
+
+    #[test]
+    fn group_is_left_alone_when_any_member_cant_be_changed() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_group(String::from("Goblin"), 12, 3);
+        turn_order.set_max_hit_points(Target::member(0, 0), 7).unwrap();
+        turn_order.set_max_hit_points(Target::member(0, 1), 7).unwrap();
+
+        assert!(turn_order.damage(0, 3).is_err());
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(7), Some(7), None]);
+
+        turn_order.set_max_hit_points(Target::member(0, 2), 7).unwrap();
+        turn_order.set_uses_death_saves(Target::member(0, 2), true).unwrap();
+        turn_order.damage(Target::member(0, 0), 3).unwrap();
+        turn_order.damage(Target::member(0, 2), 14).unwrap();
+        assert!(turn_order.heal(0, 2).is_err());
+        assert_eq!(member_hit_points(&turn_order, 0), [Some(4), Some(7), Some(0)]);
+    }
+
+    #[test]
+    fn every_creature_gets_at_least_one_pass() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 25);
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.add_creature(String::from("Cade"), -2);
+        turn_order.set_initiative_system(Box::new(initiative::MultiPass));
+        turn_order.start_combat().unwrap();
+
+        let mut turns = vec![(turn_order.pass(), names(&turn_order)[turn_order.current_turn()].to_string())];
+        while turn_order.round() == 1 {
+            turn_order.next_turn().unwrap();
+            turns.push((turn_order.pass(), names(&turn_order)[turn_order.current_turn()].to_string()));
+        }
+
+        let expected = [(1, "Aria"), (1, "Brom"), (1, "Cade"), (2, "Aria"), (2, "Brom"), (3, "Aria"), (1, "Aria")];
+        let expected: Vec<(usize, String)> = expected.iter().map(|(pass, name)| (*pass, name.to_string())).collect();
+        assert_eq!(turns, expected);
+    }
+
+    // Each pass counts as one of the creature's turns, so effects can run out partway through a round
+    #[test]
+    fn effects_tick_on_every_pass() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 25);
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.set_initiative_system(Box::new(initiative::MultiPass));
+        turn_order.start_combat().unwrap();
+        turn_order.add_status_effect_timed(0, String::from("Hasted"), 2, status_effect::ClearType::EndOfTurn).unwrap();
+
+        let effects = |turn_order: &TurnOrder| turn_order.entries()[0].as_creature().unwrap().status_effects().len();
+        turn_order.next_turn().unwrap();
+        assert_eq!(effects(&turn_order), 1);
+
+        turn_order.next_turn().unwrap();
+        assert_eq!((turn_order.round(), turn_order.pass(), turn_order.current_turn()), (1, 2, 0));
+        assert_eq!(effects(&turn_order), 1);
+
+        let updates = turn_order.next_turn().unwrap();
+        assert_eq!((turn_order.round(), turn_order.pass()), (1, 2));
+        assert_eq!(effects(&turn_order), 0);
+        assert!(updates.iter().any(|update| update.contains("Hasted")), "{updates:?}");
+    }
+
+    #[test]
+    fn legendary_actions_refresh_at_the_start_of_the_creatures_turn() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 22);
+        turn_order.add_creature(String::from("Dragon"), 15);
+        turn_order.set_legendary_actions(1, 2).unwrap();
+        turn_order.start_combat().unwrap();
+
+        assert_eq!(turn_order.spend_legendary_action(1).unwrap(), "Dragon uses a legendary action (1/2 remaining).");
+        assert_eq!(turn_order.spend_legendary_action(1).unwrap(), "Dragon uses a legendary action (0/2 remaining).");
+        assert_eq!(turn_order.spend_legendary_action(1), Err("No legendary actions remaining."));
+
+        turn_order.next_turn().unwrap();
+        assert_eq!(turn_order.spend_legendary_action(1), Err("Legendary actions can only be used on another creature's turn."));
+        assert_eq!(turn_order.spend_legendary_action(0), Err("Creature has no legendary actions."));
+
+        turn_order.next_turn().unwrap();
+        assert_eq!(turn_order.spend_legendary_action(1).unwrap(), "Dragon uses a legendary action (1/2 remaining).");
+    }
+
+    #[test]
+    fn lair_action_loses_initiative_ties() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_lair_action();
+        turn_order.add_creature(String::from("Aria"), 20);
+        turn_order.add_creature(String::from("Brom"), 21);
+        turn_order.add_creature(String::from("Cade"), 19);
+        assert_eq!(names(&turn_order), ["Brom", "Aria", "Lair Action", "Cade"]);
+    }
+
+    #[test]
+    fn action_economy_resets_when_the_creatures_turn_begins() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.set_speed(1, 30).unwrap();
+        turn_order.start_combat().unwrap();
+
+        assert_eq!(turn_order.spend_bonus_action(0).unwrap(), "Aria uses its bonus action.");
+        assert_eq!(turn_order.spend_reaction(1).unwrap(), "Brom uses its reaction.");
+        assert_eq!(turn_order.spend_reaction(1), Err("Reaction already used this round."));
+        assert_eq!(turn_order.spend_movement(1, 20).unwrap(), "Brom moves 20 ft (10 ft remaining).");
+        assert_eq!(turn_order.spend_movement(1, 15), Err("Not enough movement remaining."));
+        assert_eq!(turn_order.spend_movement(0, 5), Err("Movement is not tracked, set a speed first."));
+
+        // Only the creature whose turn it is gets its actions back
+        turn_order.next_turn().unwrap();
+        let brom = turn_order.creatures().nth(1).unwrap().action_economy();
+        assert!(brom.reaction_available());
+        assert_eq!(brom.movement().unwrap().remaining(), 30);
+        assert!(!turn_order.creatures().next().unwrap().action_economy().bonus_action_available());
+
+        turn_order.next_turn().unwrap();
+        assert_eq!(turn_order.creatures().next().unwrap().action_economy().to_string(), "");
+    }
+
+    #[test]
+    fn recurring_damage_lands_every_turn() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Troll"), 10);
+        turn_order.set_max_hit_points(0, 20).unwrap();
+        turn_order.set_max_hit_points(1, 84).unwrap();
+        turn_order.damage(1, 54).unwrap();
+        let burning = RecurringEffect::damage(status_effect::ClearType::BeginningOfTurn, dice::DiceExpression::flat(3), Some(String::from("fire")));
+        turn_order.add_status_effect_recurring(0, String::from("Burning"), None, burning).unwrap();
+        let regeneration = RecurringEffect::healing(status_effect::ClearType::EndOfTurn, dice::DiceExpression::flat(10));
+        turn_order.add_status_effect_recurring(1, String::from("Regeneration"), None, regeneration).unwrap();
+
+        let updates = turn_order.start_combat().unwrap();
+        assert!(updates.iter().any(|update| update == "Burning deals 3 fire damage to Aria."), "{updates:?}");
+        let updates = turn_order.next_turn().unwrap();
+        assert!(!updates.iter().any(|update| update.contains("Regeneration")), "{updates:?}");
+        let updates = turn_order.next_turn().unwrap();
+        assert!(updates.iter().any(|update| update == "Regeneration heals Troll for 10."), "{updates:?}");
+
+        let hit_points: Vec<usize> = turn_order.creatures()
+            .map(|creature| creature.hit_points().unwrap().current())
+            .collect();
+        assert_eq!(hit_points, [14, 40]);
+    }
+
+    #[test]
+    fn recurring_effect_lasts_until_saved_against() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Brom"), 12);
+        let poison = RecurringEffect::damage(status_effect::ClearType::EndOfTurn, dice::DiceExpression::flat(2), None);
+        // A DC of 1 can't be failed and a DC of 40 can't be made
+        turn_order.add_status_effect_recurring(0, String::from("Poisoned"), None, poison.clone().with_save(1, 0)).unwrap();
+        turn_order.add_status_effect_recurring(1, String::from("Poisoned"), None, poison.with_save(40, 0)).unwrap();
+        turn_order.start_combat().unwrap();
+
+        for _ in 0..4 {
+            turn_order.next_turn().unwrap();
+        }
+
+        let effects: Vec<usize> = turn_order.creatures().map(|creature| creature.status_effects().len()).collect();
+        assert_eq!(effects, [0, 1]);
+    }
+
+    #[test]
+    fn creature_moved_below_an_event_stays_there() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 22);
+        turn_order.add_lair_action();
+        turn_order.add_creature(String::from("Brom"), 12);
+        turn_order.add_creature(String::from("Cade"), 20);
+        assert_eq!(names(&turn_order), ["Aria", "Cade", "Lair Action", "Brom"]);
+
+        turn_order.move_creature(1, 2).unwrap();
+        turn_order.add_creature(String::from("Dara"), 5);
+        assert_eq!(names(&turn_order), ["Aria", "Lair Action", "Cade", "Brom", "Dara"]);
+    }
+
+    #[test]
+    fn only_tagged_creatures_end_the_fight() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Bandit"), 12);
+        turn_order.add_creature(String::from("Villager"), 8);
+        turn_order.set_kind(0, CreatureKind::PlayerCharacter).unwrap();
+        for index in 0..3 {
+            turn_order.set_max_hit_points(index, 5).unwrap();
+        }
+        turn_order.start_combat().unwrap();
+
+        // Untagged creatures are neutral, so there are no enemies to defeat yet
+        turn_order.damage(2, 5).unwrap();
+        assert!(!turn_order.combat_over());
+
+        turn_order.set_kind(1, CreatureKind::Enemy).unwrap();
+        let updates = turn_order.damage(1, 5).unwrap();
+        assert!(updates.iter().any(|update| update == "All enemies have been defeated!"));
+        assert!(turn_order.combat_over());
+    }
+
+    #[test]
+    fn fight_is_over_when_the_party_falls() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Ogre"), 12);
+        turn_order.set_kind(0, CreatureKind::PlayerCharacter).unwrap();
+        turn_order.set_kind(1, CreatureKind::Enemy).unwrap();
+        turn_order.set_max_hit_points(0, 5).unwrap();
+        turn_order.start_combat().unwrap();
+
+        turn_order.damage(0, 5).unwrap();
+        assert_eq!(turn_order.combat_over_reason(), Some("The party has been defeated!"));
+    }
+
+    #[test]
+    fn summary_keeps_creatures_with_the_same_name_apart() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Goblin"), 14);
+        turn_order.add_creature(String::from("Goblin"), 10);
+        turn_order.set_max_hit_points(0, 7).unwrap();
+        turn_order.set_max_hit_points(1, 7).unwrap();
+        turn_order.start_combat().unwrap();
+
+        turn_order.damage(1, 4).unwrap();
+        let summary = turn_order.combat_summary();
+        let taken: Vec<(&str, usize)> = summary.creatures().iter()
+            .map(|creature| (creature.name(), creature.damage_taken()))
+            .collect();
+        assert_eq!(taken, [("Goblin", 0), ("Goblin", 4)]);
+    }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn script_damage_is_in_the_summary() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Ogre"), 12);
+        turn_order.set_max_hit_points(0, 20).unwrap();
+        turn_order.scripts_mut().add(String::from("burn"), r#"fn on_round_start(round) { damage("Ogre", 3); }"#).unwrap();
+        turn_order.start_combat().unwrap();
+
+        let summary = turn_order.combat_summary();
+        assert_eq!(summary.creatures()[0].damage_taken(), 3);
+    }
+
+    #[test]
+    fn passives_are_carried_over_from_the_roster() {
+        let mut template = CreatureTemplate::new(String::from("Aria"), 3);
+        template.set_passives(Some(14), None, Some(11));
+
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature_from_template(&template, 15);
+        let aria = turn_order.creatures().next().unwrap();
+        assert_eq!(aria.passive_perception(), Some(14));
+        assert_eq!(aria.passive_investigation(), Some(11));
+        assert_eq!(aria.to_string(), "Aria [PP 14, PInv 11]");
+    }
+}
@@ -0,0 +1,281 @@
+use super::{creature::{kind::CreatureKind, status_effect::{ClearType, TurnsLeft, ValueRule}, Creature}, TurnOrder};
+use core::fmt;
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+pub mod csv;
+pub mod improved_initiative;
+
+/*
+    File formats a turn order can be saved as or read from. Native is
+    this tracker's own JSON and keeps everything, the others are for
+    sharing with people using other tools and can only hold part of it.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Native,
+    Csv,
+    ImprovedInitiative
+}
+
+/*
+    The result of a conversion along with everything that didn't fit
+    in the other format, so nothing is dropped without the user
+    being told.
+ */
+pub struct Converted<T> {
+    value: T,
+    unsupported: Vec<String>
+}
+
+// Creature details that only some formats can hold
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Field {
+    Kind,
+    Hidden,
+    Surprised,
+    InitiativeModifier,
+    LegendaryActions,
+    DamageModifiers,
+    ConditionImmunities,
+    DeathSaves,
+    EffectDurations,
+    HiddenEffects,
+    ConditionValues,
+    RecurringEffects,
+    Passives
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Native, Format::Csv, Format::ImprovedInitiative];
+
+    /*
+        Guesses from the file extension, then for JSON from what's in
+        the file. Improved Initiative encounters list their creatures
+        under "Combatants". Files that don't exist yet are saved as
+        this tracker's own JSON.
+     */
+    pub fn from_path(path: &Path) -> Format {
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+            return Format::Csv;
+        }
+
+        let is_improved_initiative = fs::read_to_string(path).ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .is_some_and(|json| json.get("Combatants").is_some_and(|combatants| combatants.is_array()));
+        match is_improved_initiative {
+            true => Format::ImprovedInitiative,
+            false => Format::Native
+        }
+    }
+}
+
+impl<T> Converted<T> {
+    pub(crate) fn new(value: T, unsupported: Vec<String>) -> Converted<T> {
+        Converted { value, unsupported }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn unsupported(&self) -> &Vec<String> {
+        &self.unsupported
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Converted<U> {
+        Converted { value: f(self.value), unsupported: self.unsupported }
+    }
+}
+
+impl TurnOrder {
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        crate::storage::save_json(path, self)
+    }
+
+    pub fn load_from(path: &Path) -> Result<TurnOrder, String> {
+        let contents = read_file(path)?;
+        serde_json::from_str(&contents).map_err(|e| format!("Could not parse {}: {e}", path.display()))
+    }
+
+    // Returns what couldn't be written in that format
+    pub fn export(&self, path: &Path, format: Format) -> Result<Vec<String>, String> {
+        let converted = match format {
+            Format::Native => return self.save_to(path).map(|_| Vec::new()),
+            Format::Csv => self.to_csv(),
+            Format::ImprovedInitiative => self.to_improved_initiative()?
+        };
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+        }
+        fs::write(path, converted.value()).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+
+        Ok(converted.unsupported)
+    }
+
+    pub fn import(path: &Path, format: Format) -> Result<Converted<TurnOrder>, String> {
+        match format {
+            Format::Native => TurnOrder::load_from(path).map(|turn_order| Converted::new(turn_order, Vec::new())),
+            Format::Csv => TurnOrder::from_csv(&read_file(path)?),
+            Format::ImprovedInitiative => TurnOrder::from_improved_initiative(&read_file(path)?)
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self {
+            Format::Native => "JSON",
+            Format::Csv => "CSV",
+            Format::ImprovedInitiative => "Improved Initiative"
+        };
+
+        write!(f, "{format}")
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" | "native" => Ok(Format::Native),
+            "csv" => Ok(Format::Csv),
+            "ii" | "improved initiative" | "improved-initiative" | "improvedinitiative" => Ok(Format::ImprovedInitiative),
+            _ => Err(format!("Unknown format \"{s}\", expected json, csv or ii."))
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self {
+            Field::Kind => "Creature kinds",
+            Field::Hidden => "Hidden creatures",
+            Field::Surprised => "Surprised creatures",
+            Field::InitiativeModifier => "Initiative modifiers",
+            Field::LegendaryActions => "Legendary actions",
+            Field::DamageModifiers => "Resistances, immunities and vulnerabilities",
+            Field::ConditionImmunities => "Condition immunities",
+            Field::DeathSaves => "Death saves",
+            Field::EffectDurations => "Effect durations",
+            Field::HiddenEffects => "Hidden effects",
+            Field::ConditionValues => "Condition value rules",
+            Field::RecurringEffects => "Recurring effects",
+            Field::Passives => "Passive scores"
+        };
+
+        write!(f, "{field}")
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))
+}
+
+// Every creature detail set to something other than its default, which a format may not be able to hold
+fn creature_fields(creature: &Creature) -> Vec<Field> {
+    let effects = creature.status_effects();
+    let checks = [
+        (Field::Kind, creature.kind() != CreatureKind::default()),
+        (Field::Hidden, creature.hidden()),
+        (Field::Surprised, creature.surprised()),
+        (Field::InitiativeModifier, creature.initiative_modifier() != 0),
+        (Field::LegendaryActions, creature.legendary_actions().is_some()),
+        (Field::DamageModifiers, !creature.damage_modifiers().is_empty()),
+        (Field::ConditionImmunities, !creature.condition_immunities().is_empty()),
+        (Field::DeathSaves, creature.uses_death_saves() || creature.downed().is_some()),
+        (Field::EffectDurations, effects.iter().any(|effect| matches!(effect.turns_left(), TurnsLeft::Finite(_)))),
+        (Field::HiddenEffects, effects.iter().any(|effect| effect.hidden())),
+        (Field::ConditionValues, effects.iter().any(|effect| effect.value_rule().is_some_and(|rule| rule != ValueRule::default()))),
+        (Field::RecurringEffects, effects.iter().any(|effect| effect.recurring().is_some())),
+        (Field::Passives, [creature.passive_perception(), creature.passive_insight(), creature.passive_investigation()].iter().any(Option::is_some))
+    ];
+
+    checks.into_iter()
+        .filter_map(|(field, is_set)| is_set.then_some(field))
+        .collect()
+}
+
+/*
+    Adds an effect read from another format, where valued conditions
+    are written as their name and value, such as "Frightened 2". Those
+    come back as valued conditions with the default rule, which count
+    down on their own instead of using a duration. Returns the new
+    effect's index.
+ */
+pub(crate) fn add_imported_effect(creature: &mut Creature, text: &str, duration: Option<(usize, ClearType)>) -> usize {
+    let valued = text.trim().rsplit_once(' ')
+        .and_then(|(name, value)| Some((name.trim(), value.parse::<usize>().ok()?)))
+        .filter(|(name, value)| !name.is_empty() && *value > 0);
+
+    match (valued, duration) {
+        (Some((name, value)), _) => creature.add_status_effect_valued(name.to_string(), value, ValueRule::default()),
+        (None, Some((turns, clear_type))) => creature.add_status_effect_timed(text.trim().to_string(), turns, clear_type),
+        (None, None) => creature.add_status_effect(text.trim().to_string())
+    }
+
+    creature.status_effects().len() - 1
+}
+
+/*
+    Lists the creature details a format couldn't hold, one line per
+    detail naming every creature that lost it.
+ */
+pub(crate) fn report_lost_fields<'a>(
+    creatures: impl Iterator<Item = &'a Creature>,
+    format: Format,
+    supported: impl Fn(Field, &Creature) -> bool
+) -> Vec<String> {
+    let mut lost: BTreeMap<Field, Vec<&str>> = BTreeMap::new();
+    for creature in creatures {
+        for field in creature_fields(creature) {
+            if !supported(field, creature) {
+                lost.entry(field).or_default().push(creature.name());
+            }
+        }
+    }
+
+    lost.into_iter()
+        .map(|(field, names)| format!("{field} are not supported by {format}: {}.", names.join(", ")))
+        .collect()
+}
+
+// Turn order details outside of the creatures themselves that a format couldn't hold
+pub(crate) fn report_lost_order_details(turn_order: &TurnOrder, format: Format, keeps_groups: bool, keeps_round: bool) -> Vec<String> {
+    let mut lost: Vec<String> = Vec::new();
+
+    let events: Vec<&str> = turn_order.entries.iter()
+        .filter(|entry| entry.is_event())
+        .map(|entry| entry.name())
+        .collect();
+    if !events.is_empty() {
+        lost.push(format!("Events are not supported by {format}: {}.", events.join(", ")));
+    }
+
+    let groups: Vec<&str> = turn_order.entries.iter()
+        .filter(|entry| entry.as_group().is_some())
+        .map(|entry| entry.name())
+        .collect();
+    if !keeps_groups && !groups.is_empty() {
+        lost.push(format!("Groups are not supported by {format} and were split into their members: {}.", groups.join(", ")));
+    }
+
+    let system = turn_order.initiative_system.name();
+    if system != "Standard" {
+        lost.push(format!("The {system} initiative system is not supported by {format}."));
+    }
+
+    if turn_order.combat_started {
+        if !keeps_round {
+            lost.push(format!("The round and current turn are not supported by {format}."));
+        }
+        lost.push(format!("The combat summary is not supported by {format}."));
+    }
+
+    lost
+}
@@ -0,0 +1,191 @@
+use super::{add_imported_effect, report_lost_fields, report_lost_order_details, Converted, Format};
+use crate::turn_order::{creature::Creature, entry::TurnEntry, TurnOrder};
+use std::collections::BTreeSet;
+
+/*
+    A plain spreadsheet of one creature per row:
+
+        name,initiative,hp,effects
+        Goblin 1,14,5/7,Prone; Frightened 2
+        Aria,18,,
+
+    HP is "current/max", just the max, or empty when it isn't tracked.
+    Effects are separated by semicolons. The header row is optional
+    when the columns are in that order, and columns it doesn't name
+    are reported rather than read.
+ */
+const HEADER: [&str; 4] = ["name", "initiative", "hp", "effects"];
+
+enum Column {
+    Name,
+    Initiative,
+    HitPoints,
+    Effects,
+    Unsupported(String)
+}
+
+impl TurnOrder {
+    pub fn to_csv(&self) -> Converted<String> {
+        let mut csv = HEADER.join(",");
+        csv.push('\n');
+
+        for creature in self.creatures() {
+            let hit_points = creature.hit_points()
+                .map(|hit_points| format!("{}/{}", hit_points.current(), hit_points.max()))
+                .unwrap_or_default();
+            let effects: Vec<String> = creature.status_effects().iter()
+                .map(|effect| match effect.value() {
+                    Some(value) => format!("{} {value}", effect.name()),
+                    None => effect.name().to_string()
+                })
+                .collect();
+
+            let row = [creature.name().to_string(), creature.initiative().to_string(), hit_points, effects.join("; ")];
+            let row: Vec<String> = row.iter().map(|cell| escape_cell(cell)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        let mut unsupported = report_lost_order_details(self, Format::Csv, false, false);
+        unsupported.append(&mut report_lost_fields(self.creatures(), Format::Csv, |_, _| false));
+
+        Converted::new(csv, unsupported)
+    }
+
+    pub fn from_csv(csv: &str) -> Result<Converted<TurnOrder>, String> {
+        let mut rows = csv.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(line_index, line)| (line_index + 1, split_row(line)));
+
+        let mut unsupported: BTreeSet<String> = BTreeSet::new();
+        let mut turn_order = TurnOrder::new();
+
+        let Some((first_line, first_row)) = rows.next() else {
+            return Ok(Converted::new(turn_order, Vec::new()));
+        };
+
+        let has_header = first_row.first().is_some_and(|cell| cell.trim().eq_ignore_ascii_case("name"));
+        let columns: Vec<Column> = match has_header {
+            true => first_row.iter().map(|cell| Column::from_header(cell)).collect(),
+            false => vec![Column::Name, Column::Initiative, Column::HitPoints, Column::Effects]
+        };
+
+        for column in &columns {
+            if let Column::Unsupported(name) = column {
+                unsupported.insert(format!("Column \"{name}\" is not supported by CSV import and was skipped."));
+            }
+        }
+
+        // Without a header the first row is a creature like the rest
+        let first_data_row = match has_header {
+            true => None,
+            false => Some((first_line, first_row))
+        };
+
+        for (line, row) in first_data_row.into_iter().chain(rows) {
+            if row.len() > columns.len() {
+                unsupported.insert(format!("Cells past column {} are not supported by CSV import and were skipped.", columns.len()));
+            }
+
+            let creature = read_row(&columns, &row).map_err(|e| format!("Line {line}: {e}"))?;
+            turn_order.entries.push(TurnEntry::Creature(creature));
+        }
+
+        turn_order.reorder_creatures();
+        Ok(Converted::new(turn_order, unsupported.into_iter().collect()))
+    }
+}
+
+impl Column {
+    fn from_header(header: &str) -> Column {
+        match header.trim().to_lowercase().as_str() {
+            "name" => Column::Name,
+            "initiative" | "init" => Column::Initiative,
+            "hp" | "hit points" => Column::HitPoints,
+            "effects" | "conditions" => Column::Effects,
+            other => Column::Unsupported(other.to_string())
+        }
+    }
+}
+
+fn read_row(columns: &[Column], row: &[String]) -> Result<Creature, String> {
+    let mut name: Option<String> = None;
+    let mut initiative: isize = 0;
+    let mut hit_points: Option<(usize, usize)> = None;
+    let mut effects: Vec<String> = Vec::new();
+
+    for (column, cell) in columns.iter().zip(row) {
+        let cell = cell.trim();
+        if cell.is_empty() {
+            continue;
+        }
+
+        match column {
+            Column::Name => name = Some(cell.to_string()),
+            Column::Initiative => initiative = cell.parse().map_err(|_| format!("Invalid initiative \"{cell}\"."))?,
+            Column::HitPoints => hit_points = Some(parse_hit_points(cell)?),
+            Column::Effects => effects = cell.split(';')
+                .map(|effect| effect.trim().to_string())
+                .filter(|effect| !effect.is_empty())
+                .collect(),
+            Column::Unsupported(_) => ()
+        }
+    }
+
+    let mut creature = Creature::new(name.ok_or("Missing creature name.")?, initiative);
+    if let Some((current, max)) = hit_points {
+        creature.set_hit_points(current, max);
+    }
+    for effect in effects {
+        add_imported_effect(&mut creature, &effect, None);
+    }
+
+    Ok(creature)
+}
+
+// "5/7" for current and max, or "7" when at full HP
+fn parse_hit_points(cell: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid HP \"{cell}\", expected current/max or max.");
+
+    match cell.split_once('/') {
+        Some((current, max)) => Ok((
+            current.trim().parse().map_err(|_| invalid())?,
+            max.trim().parse().map_err(|_| invalid())?
+        )),
+        None => {
+            let max = cell.parse().map_err(|_| invalid())?;
+            Ok((max, max))
+        }
+    }
+}
+
+// Cells with commas, quotes or leading spaces are quoted, with quotes doubled
+fn escape_cell(cell: &str) -> String {
+    match cell.contains([',', '"', '\n']) || cell.starts_with(' ') {
+        true => format!("\"{}\"", cell.replace('"', "\"\"")),
+        false => cell.to_string()
+    }
+}
+
+fn split_row(line: &str) -> Vec<String> {
+    let mut cells: Vec<String> = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => cells.push(std::mem::take(&mut cell)),
+            (c, _) => cell.push(c)
+        }
+    }
+    cells.push(cell);
+
+    cells
+}
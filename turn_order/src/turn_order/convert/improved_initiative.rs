@@ -0,0 +1,309 @@
+use super::{add_imported_effect, report_lost_fields, report_lost_order_details, Converted, Field, Format};
+use crate::turn_order::{
+    creature::{damage_modifiers::DamageModifier, kind::CreatureKind, status_effect::{ClearType, TurnsLeft}, Creature},
+    entry::TurnEntry,
+    TurnOrder
+};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+
+/*
+    Saved encounters from Improved Initiative, the open source web
+    tracker. The parts read and written are:
+
+        {
+            "Name": "...",
+            "Combatants": [{
+                "Id": "...",
+                "StatBlock": {
+                    "Name": "Goblin", "HP": { "Value": 7 }, "InitiativeModifier": 2,
+                    "Player": "player" or "", "ConditionImmunities": [],
+                    "DamageResistances": [], "DamageImmunities": [], "DamageVulnerabilities": []
+                },
+                "MaxHP": 7, "CurrentHP": 5, "Initiative": 14, "InitiativeGroup": null,
+                "Alias": "", "IndexLabel": 1, "Hidden": false,
+                "Tags": [{ "Text": "Prone", "DurationRemaining": 0, "DurationTiming": null, "Hidden": false }]
+            }],
+            "RoundCounter": 0,
+            "ActiveCombatantId": null
+        }
+
+    Anything else with a value, such as stat block actions or AC, is
+    reported as unsupported.
+ */
+const ENCOUNTER_FIELDS: [&str; 7] = ["Name", "Id", "Path", "Version", "Combatants", "RoundCounter", "ActiveCombatantId"];
+const COMBATANT_FIELDS: [&str; 14] = [
+    "Id", "StatBlock", "MaxHP", "CurrentHP", "Initiative", "InitiativeGroup", "Alias",
+    "IndexLabel", "Hidden", "Tags", "InterfaceVersion", "RevealedAC", "TemporaryHP", "ImageURL"
+];
+const STAT_BLOCK_FIELDS: [&str; 13] = [
+    "Id", "Name", "Path", "Source", "Type", "Version", "HP", "InitiativeModifier", "Player",
+    "ConditionImmunities", "DamageResistances", "DamageImmunities", "DamageVulnerabilities"
+];
+const PLAYER: &str = "player";
+
+impl TurnOrder {
+    pub fn to_improved_initiative(&self) -> Result<Converted<String>, String> {
+        let mut combatants: Vec<Value> = Vec::new();
+        let mut active_id = Value::Null;
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let group_id = entry.as_group().map(|_| Value::String(format!("group-{index}")));
+            for (member_index, creature) in entry.creatures().into_iter().enumerate() {
+                let id = format!("{index}-{member_index}");
+                if index == self.current_turn && member_index == 0 && self.combat_started {
+                    active_id = Value::String(id.clone());
+                }
+
+                let base_name = match entry.as_group() {
+                    Some(group) => group.name(),
+                    None => creature.name()
+                };
+                combatants.push(combatant_json(creature, id, base_name, group_id.clone().unwrap_or(Value::Null)));
+            }
+        }
+
+        let encounter = json!({
+            "Name": "Initiative Tracker Export",
+            "Combatants": combatants,
+            "RoundCounter": if self.combat_started { self.round } else { 0 },
+            "ActiveCombatantId": active_id
+        });
+        let json = serde_json::to_string_pretty(&encounter).map_err(|e| e.to_string())?;
+
+        let mut unsupported = report_lost_order_details(self, Format::ImprovedInitiative, true, true);
+        unsupported.append(&mut report_lost_fields(self.creatures(), Format::ImprovedInitiative, supports_field));
+
+        Ok(Converted::new(json, unsupported))
+    }
+
+    pub fn from_improved_initiative(json: &str) -> Result<Converted<TurnOrder>, String> {
+        let encounter: Value = serde_json::from_str(json).map_err(|e| format!("Could not parse Improved Initiative file: {e}"))?;
+        let encounter = encounter.as_object().ok_or("Expected an Improved Initiative encounter object.")?;
+
+        let mut unsupported: BTreeSet<String> = BTreeSet::new();
+        report_unknown_fields(encounter, &ENCOUNTER_FIELDS, "Encounter ", &mut unsupported);
+
+        let combatants = match encounter.get("Combatants") {
+            Some(Value::Array(combatants)) => combatants.as_slice(),
+            Some(_) => return Err(String::from("Combatants should be a list.")),
+            None => &[]
+        };
+
+        // Combatants sharing a stat block are told apart by their index labels
+        let base_names: Vec<&str> = combatants.iter()
+            .map(|combatant| combatant.pointer("/StatBlock/Name").and_then(Value::as_str).unwrap_or_default())
+            .collect();
+        let mut turn_order = TurnOrder::new();
+        let mut active_id: Option<u64> = None;
+
+        for (combatant, base_name) in combatants.iter().zip(&base_names) {
+            let combatant = combatant.as_object().ok_or("Each combatant should be an object.")?;
+            let shares_name = base_names.iter().filter(|other| *other == base_name).count() > 1;
+
+            let creature = read_combatant(combatant, shares_name, &mut unsupported)?;
+            if combatant.get("Id").is_some_and(|id| !id.is_null() && encounter.get("ActiveCombatantId") == Some(id)) {
+                active_id = Some(creature.id());
+            }
+            turn_order.entries.push(TurnEntry::Creature(creature));
+        }
+
+        turn_order.reorder_creatures();
+
+        let round = encounter.get("RoundCounter").and_then(Value::as_u64).unwrap_or(0) as usize;
+        if round > 0 {
+            turn_order.round = round;
+            turn_order.combat_started = true;
+            for creature in turn_order.entries.iter().flat_map(|entry| entry.creatures()) {
+                turn_order.summary.creature_mut(creature.id(), creature.name(), creature.kind());
+            }
+            // Found by id, since unlabelled combatants can share a name
+            if let Some(active_id) = active_id {
+                turn_order.current_turn = turn_order.entries.iter()
+                    .position(|entry| entry.id() == active_id)
+                    .unwrap_or(0);
+            }
+        }
+
+        Ok(Converted::new(turn_order, unsupported.into_iter().collect()))
+    }
+}
+
+fn supports_field(field: Field, creature: &Creature) -> bool {
+    match field {
+        // Improved Initiative only tells players apart from everyone else, who come back untagged
+        Field::Kind => matches!(creature.kind(), CreatureKind::PlayerCharacter | CreatureKind::Neutral),
+        Field::Hidden
+        | Field::InitiativeModifier
+        | Field::DamageModifiers
+        | Field::ConditionImmunities
+        | Field::EffectDurations
+        | Field::HiddenEffects => true,
+        // Players are given death saves again when imported, but not their progress
+        Field::DeathSaves => creature.kind() == CreatureKind::PlayerCharacter && creature.downed().is_none(),
+        _ => false
+    }
+}
+
+fn combatant_json(creature: &Creature, id: String, base_name: &str, group_id: Value) -> Value {
+    let (current, max) = creature.hit_points()
+        .map(|hit_points| (hit_points.current(), hit_points.max()))
+        .unwrap_or((0, 0));
+    let player = match creature.kind() {
+        CreatureKind::PlayerCharacter => PLAYER,
+        _ => ""
+    };
+    let damage_modifiers = creature.damage_modifiers();
+
+    let tags: Vec<Value> = creature.status_effects().iter()
+        .map(|effect| {
+            let text = match effect.value() {
+                Some(value) => format!("{} {value}", effect.name()),
+                None => effect.name().to_string()
+            };
+            let (duration, timing) = match (effect.turns_left(), effect.clear_type()) {
+                (TurnsLeft::Finite(turns), ClearType::BeginningOfTurn) => (*turns, json!("StartOfTurn")),
+                (TurnsLeft::Finite(turns), ClearType::EndOfTurn) => (*turns, json!("EndOfTurn")),
+                (TurnsLeft::Indefinite, _) => (0, Value::Null)
+            };
+
+            json!({
+                "Text": text,
+                "DurationRemaining": duration,
+                "DurationTiming": timing,
+                "DurationCCId": if duration > 0 { json!(id) } else { Value::Null },
+                "Hidden": effect.hidden()
+            })
+        })
+        .collect();
+
+    json!({
+        "Id": id,
+        "StatBlock": {
+            "Name": base_name,
+            "HP": { "Value": max, "Notes": "" },
+            "InitiativeModifier": creature.initiative_modifier(),
+            "Player": player,
+            "ConditionImmunities": creature.condition_immunities(),
+            "DamageResistances": damage_modifiers.resistances(),
+            "DamageImmunities": damage_modifiers.immunities(),
+            "DamageVulnerabilities": damage_modifiers.vulnerabilities()
+        },
+        "MaxHP": max,
+        "CurrentHP": current,
+        "TemporaryHP": 0,
+        "Initiative": creature.initiative(),
+        "InitiativeGroup": group_id,
+        "Alias": if creature.name() != base_name { creature.name() } else { "" },
+        "IndexLabel": Value::Null,
+        "Tags": tags,
+        "Hidden": creature.hidden(),
+        "RevealedAC": false
+    })
+}
+
+fn read_combatant(combatant: &Map<String, Value>, shares_name: bool, unsupported: &mut BTreeSet<String>) -> Result<Creature, String> {
+    report_unknown_fields(combatant, &COMBATANT_FIELDS, "Combatant ", unsupported);
+
+    let empty = Map::new();
+    let stat_block = combatant.get("StatBlock").and_then(Value::as_object).unwrap_or(&empty);
+    report_unknown_fields(stat_block, &STAT_BLOCK_FIELDS, "Stat block ", unsupported);
+
+    let base_name = stat_block.get("Name").and_then(Value::as_str).unwrap_or("Unnamed");
+    let alias = combatant.get("Alias").and_then(Value::as_str).filter(|alias| !alias.trim().is_empty());
+    let name = match (alias, combatant.get("IndexLabel").and_then(Value::as_u64)) {
+        (Some(alias), _) => alias.to_string(),
+        (None, Some(label)) if shares_name => format!("{base_name} {label}"),
+        (None, _) => base_name.to_string()
+    };
+
+    let initiative = combatant.get("Initiative").and_then(Value::as_i64).unwrap_or(0) as isize;
+    let mut creature = Creature::new(name, initiative);
+
+    let max = combatant.get("MaxHP").and_then(Value::as_u64)
+        .or_else(|| stat_block.get("HP").and_then(|hit_points| hit_points.get("Value")).and_then(Value::as_u64))
+        .unwrap_or(0) as usize;
+    let current = combatant.get("CurrentHP").and_then(Value::as_u64).map_or(max, |current| current as usize);
+    if max > 0 {
+        creature.set_hit_points(current, max);
+    }
+    if combatant.get("TemporaryHP").and_then(Value::as_u64).is_some_and(|temporary| temporary > 0) {
+        unsupported.insert(String::from("Temporary HP is not supported and was skipped."));
+    }
+    if combatant.get("InitiativeGroup").is_some_and(|group| !is_empty(group)) {
+        unsupported.insert(String::from("Linked initiative groups are not supported, their members were imported as separate creatures."));
+    }
+
+    if stat_block.get("Player").and_then(Value::as_str) == Some(PLAYER) {
+        creature.set_kind(CreatureKind::PlayerCharacter);
+        creature.set_uses_death_saves(true);
+    }
+    creature.set_initiative_modifier(stat_block.get("InitiativeModifier").and_then(Value::as_i64).unwrap_or(0) as isize);
+    creature.set_hidden(combatant.get("Hidden").and_then(Value::as_bool).unwrap_or(false));
+    creature.set_condition_immunities(string_list(stat_block.get("ConditionImmunities")));
+
+    let damage_modifiers = creature.damage_modifiers_mut();
+    damage_modifiers.set(DamageModifier::Resistance, string_list(stat_block.get("DamageResistances")));
+    damage_modifiers.set(DamageModifier::Immunity, string_list(stat_block.get("DamageImmunities")));
+    damage_modifiers.set(DamageModifier::Vulnerability, string_list(stat_block.get("DamageVulnerabilities")));
+
+    let tags = combatant.get("Tags").and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[]);
+    for tag in tags {
+        read_tag(&mut creature, tag, combatant.get("Id"), unsupported);
+    }
+
+    Ok(creature)
+}
+
+// Tags can be plain strings in older files
+fn read_tag(creature: &mut Creature, tag: &Value, combatant_id: Option<&Value>, unsupported: &mut BTreeSet<String>) {
+    if let Some(text) = tag.as_str() {
+        add_imported_effect(creature, text, None);
+        return;
+    }
+
+    let Some(text) = tag.get("Text").and_then(Value::as_str) else {
+        return;
+    };
+    let duration = tag.get("DurationRemaining").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let clear_type = match tag.get("DurationTiming").and_then(Value::as_str) {
+        Some("EndOfTurn") => ClearType::EndOfTurn,
+        _ => ClearType::BeginningOfTurn
+    };
+
+    let index = add_imported_effect(creature, text, (duration > 0).then_some((duration, clear_type)));
+    if tag.get("Hidden").and_then(Value::as_bool).unwrap_or(false) {
+        let _ = creature.set_status_effect_hidden(index, true);
+    }
+
+    // Durations counted on someone else's turn are counted on the creature's own turn instead
+    let counted_on = tag.get("DurationCCId").filter(|id| !id.is_null());
+    if duration > 0 && counted_on.is_some() && counted_on != combatant_id {
+        unsupported.insert(String::from("Effect durations tied to another combatant's turn are counted on the affected creature's turn."));
+    }
+}
+
+fn report_unknown_fields(object: &Map<String, Value>, known: &[&str], prefix: &str, unsupported: &mut BTreeSet<String>) {
+    for (field, value) in object {
+        if !known.contains(&field.as_str()) && !is_empty(value) {
+            unsupported.insert(format!("{prefix}field \"{field}\" is not supported and was skipped."));
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(flag) => !flag,
+        Value::String(text) => text.is_empty(),
+        Value::Array(list) => list.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        Value::Number(_) => false
+    }
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value.and_then(Value::as_array)
+        .map(|list| list.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
@@ -1,13 +1,61 @@
+pub mod action_economy;
+pub mod damage_modifiers;
+pub mod death_saves;
+pub mod hit_points;
+pub mod kind;
+pub mod recurring_effect;
 pub mod status_effect;
-use status_effect::{StatusEffect, TurnsLeft, ClearType, DurationStatus};
+use action_economy::ActionEconomy;
+use damage_modifiers::{DamageModifier, DamageModifiers};
+use death_saves::{DeathSaveResult, Downed};
+use hit_points::HitPoints;
+use kind::CreatureKind;
+use recurring_effect::{RecurringEffect, RecurringKind, SaveResult};
+use status_effect::{StatusEffect, TurnsLeft, ClearType, DurationStatus, ValueRule};
+use super::entry::new_id;
 use core::fmt;
+use serde::{Deserialize, Serialize};
 
 
+#[derive(Serialize, Deserialize)]
 pub struct Creature {
+    #[serde(default = "new_id")]
+    id: u64,
     name: String,
     initiative: isize,
+    // Added to a d20 when initiative is rolled again, such as every round
+    initiative_modifier: isize,
+    // Extra cards or dice drawn when initiative is dealt again, keeping the best
+    extra_initiative_draws: usize,
     status_effects: Vec<status_effect::StatusEffect>,
-    next_effect_id: usize
+    next_effect_id: usize,
+    // Set when the creature was moved by hand rather than sorted by initiative
+    manually_placed: bool,
+    // Hidden creatures are left out of player-facing views
+    hidden: bool,
+    // Surprised creatures can't act during the first round
+    surprised: bool,
+    legendary_actions: Option<LegendaryActions>,
+    // None when HP isn't being tracked for this creature
+    hit_points: Option<HitPoints>,
+    action_economy: ActionEconomy,
+    // Player characters make death saves at 0 HP instead of simply going down
+    uses_death_saves: bool,
+    downed: Option<Downed>,
+    kind: CreatureKind,
+    // Conditions that can't be applied as status effects, such as "Poisoned"
+    condition_immunities: Vec<String>,
+    damage_modifiers: DamageModifiers,
+    // Carried over from the party roster for the DM's reference
+    passive_perception: Option<usize>,
+    passive_insight: Option<usize>,
+    passive_investigation: Option<usize>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LegendaryActions {
+    max: usize,
+    remaining: usize
 }
 
 pub enum CreatureUpdate {
@@ -15,10 +63,30 @@ pub enum CreatureUpdate {
     NoUpdate
 }
 
+impl CreatureUpdate {
+    pub fn from_vec(updates: Vec<String>) -> CreatureUpdate {
+        match updates.len() {
+            0 => CreatureUpdate::NoUpdate,
+            _ => CreatureUpdate::Updates(updates)
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            CreatureUpdate::Updates(updates) => updates,
+            CreatureUpdate::NoUpdate => Vec::new()
+        }
+    }
+}
+
 impl Creature {
     pub fn new(name: String, initiative: isize) -> Creature {
         Creature {
-            name, initiative, status_effects: Vec::new(), next_effect_id: 0
+            id: new_id(), name, initiative, initiative_modifier: 0, extra_initiative_draws: 0, status_effects: Vec::new(), next_effect_id: 0,
+            manually_placed: false, hidden: false, surprised: false, legendary_actions: None,
+            hit_points: None, action_economy: ActionEconomy::new(), uses_death_saves: false, downed: None,
+            kind: CreatureKind::default(), condition_immunities: Vec::new(), damage_modifiers: DamageModifiers::new(),
+            passive_perception: None, passive_insight: None, passive_investigation: None
         }
     }
 
@@ -29,6 +97,15 @@ impl Creature {
         self.add_status_effect_to_list(effect);
     }
 
+    // Hidden effects are only shown on the DM's views
+    pub fn add_status_effect_hidden(&mut self, name: String) {
+        let effect = StatusEffect::builder(self.get_status_id(), name, status_effect::TurnsLeft::Indefinite)
+            .hidden(true)
+            .build();
+
+        self.add_status_effect_to_list(effect);
+    }
+
     pub fn add_status_effect_timed(&mut self, name: String, turn_duration: usize, clear_type: ClearType) {
         let duration = TurnsLeft::Finite(turn_duration);
         let effect = StatusEffect::builder(
@@ -41,10 +118,57 @@ impl Creature {
         self.add_status_effect_to_list(effect);
     }
 
+    // Valued conditions such as Frightened 2, which last until their value reaches 0
+    pub fn add_status_effect_valued(&mut self, name: String, value: usize, rule: ValueRule) {
+        let effect = StatusEffect::builder(self.get_status_id(), name, status_effect::TurnsLeft::Indefinite)
+            .value(value, rule)
+            .build();
+
+        self.add_status_effect_to_list(effect);
+    }
+
+    // Ongoing damage or healing, lasting until saved against if no duration is given
+    pub fn add_status_effect_recurring(&mut self, name: String, duration: Option<usize>, recurring: RecurringEffect) {
+        let turns_left = match duration {
+            Some(turns) => TurnsLeft::Finite(turns),
+            None => TurnsLeft::Indefinite
+        };
+        let effect = StatusEffect::builder(self.get_status_id(), name, turns_left)
+            .clear_type(recurring.trigger())
+            .recurring(recurring)
+            .build();
+
+        self.add_status_effect_to_list(effect);
+    }
+
+    // If Ok, returns the update to announce
+    pub fn adjust_condition_value(&mut self, effect_index: usize, change: isize) -> Result<String, &'static str> {
+        let effect = self.status_effects.get_mut(effect_index).ok_or("Status effect index out of range.")?;
+        let effect_id = effect.id();
+
+        match effect.adjust_value(change)? {
+            DurationStatus::NonExpired => {
+                let value = effect.value().unwrap_or_default();
+                Ok(format!("{} is now {value} for creature {}.", effect.name(), self.name))
+            }
+            DurationStatus::Expired => {
+                let updates = self.return_creature_updates(&vec![effect_id]).into_vec();
+                Ok(updates.join(" "))
+            }
+        }
+    }
+
     pub fn begin_turn(&mut self) -> CreatureUpdate {
         // List of indices of items to remove from the status effects Vec
         let mut effects_to_remove: Vec<usize> = Vec::new();
 
+        if let Some(ref mut legendary_actions) = self.legendary_actions {
+            legendary_actions.remaining = legendary_actions.max;
+        }
+
+        self.action_economy.reset();
+
+        let mut updates = self.apply_recurring_effects(ClearType::BeginningOfTurn);
 
         for effect in &mut self.status_effects {
             match effect.begin_turn() {
@@ -55,33 +179,385 @@ impl Creature {
             }
         }
 
-
-        self.return_creature_updates(&effects_to_remove)
+        updates.append(&mut self.return_creature_updates(&effects_to_remove).into_vec());
+        CreatureUpdate::from_vec(updates)
     }
 
     pub fn end_turn(&mut self) -> CreatureUpdate {
         let mut effects_to_remove: Vec<usize> = Vec::new();
+        let mut value_updates = self.apply_recurring_effects(ClearType::EndOfTurn);
+        value_updates.append(&mut self.roll_recurring_saves());
 
         for effect in &mut self.status_effects {
+            let value_before = effect.value();
             match effect.end_turn() {
-                DurationStatus::NonExpired => (),
+                DurationStatus::NonExpired => {
+                    if let Some(value) = effect.value().filter(|value| Some(*value) != value_before) {
+                        value_updates.push(format!("{} is now {value} for creature {}.", effect.name(), self.name));
+                    }
+                }
                 DurationStatus::Expired => {
                     effects_to_remove.push(effect.id());
                 }
             }
         }
 
-        self.return_creature_updates(&effects_to_remove)
+        value_updates.append(&mut self.return_creature_updates(&effects_to_remove).into_vec());
+        CreatureUpdate::from_vec(value_updates)
     }
 
     pub fn initiative(&self) -> isize {
         self.initiative
     }
 
+    // A new roll replaces any manual placement
+    pub fn set_initiative(&mut self, initiative: isize) {
+        self.initiative = initiative;
+        self.manually_placed = false;
+    }
+
+    pub fn initiative_modifier(&self) -> isize {
+        self.initiative_modifier
+    }
+
+    pub fn set_initiative_modifier(&mut self, initiative_modifier: isize) {
+        self.initiative_modifier = initiative_modifier;
+    }
+
+    pub fn extra_initiative_draws(&self) -> usize {
+        self.extra_initiative_draws
+    }
+
+    // 1 for Level-Headed, 2 for Improved Level-Headed
+    pub fn set_extra_initiative_draws(&mut self, extra_initiative_draws: usize) {
+        self.extra_initiative_draws = extra_initiative_draws;
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn manually_placed(&self) -> bool {
+        self.manually_placed
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn surprised(&self) -> bool {
+        self.surprised
+    }
+
+    pub fn set_surprised(&mut self, surprised: bool) {
+        self.surprised = surprised;
+    }
+
+    pub fn hit_points(&self) -> Option<&HitPoints> {
+        self.hit_points.as_ref()
+    }
+
+    // Down at 0 HP, creatures without tracked HP are never considered defeated
+    pub fn is_defeated(&self) -> bool {
+        self.hit_points.as_ref().is_some_and(|hit_points| hit_points.is_down())
+    }
+
+    pub fn kind(&self) -> CreatureKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: CreatureKind) {
+        self.kind = kind;
+    }
+
+    pub fn condition_immunities(&self) -> &Vec<String> {
+        &self.condition_immunities
+    }
+
+    pub fn set_condition_immunities(&mut self, condition_immunities: Vec<String>) {
+        self.condition_immunities = condition_immunities;
+    }
+
+    pub fn damage_modifiers(&self) -> &DamageModifiers {
+        &self.damage_modifiers
+    }
+
+    pub fn damage_modifiers_mut(&mut self) -> &mut DamageModifiers {
+        &mut self.damage_modifiers
+    }
+
+    pub fn set_damage_modifiers(&mut self, damage_modifiers: DamageModifiers) {
+        self.damage_modifiers = damage_modifiers;
+    }
+
+    pub fn passive_perception(&self) -> Option<usize> {
+        self.passive_perception
+    }
+
+    pub fn passive_insight(&self) -> Option<usize> {
+        self.passive_insight
+    }
+
+    pub fn passive_investigation(&self) -> Option<usize> {
+        self.passive_investigation
+    }
+
+    pub fn set_passives(&mut self, perception: Option<usize>, insight: Option<usize>, investigation: Option<usize>) {
+        self.passive_perception = perception;
+        self.passive_insight = insight;
+        self.passive_investigation = investigation;
+    }
+
+    // Matched against status effect names, ignoring case
+    pub fn is_immune_to(&self, effect_name: &str) -> bool {
+        self.condition_immunities.iter().any(|condition| condition.eq_ignore_ascii_case(effect_name.trim()))
+    }
+
+    // Starts tracking HP at full health
+    pub fn set_max_hit_points(&mut self, max: usize) {
+        self.hit_points = Some(HitPoints::new(max));
+    }
+
+    // For restoring a creature part way through a fight, such as from another tracker's file
+    pub fn set_hit_points(&mut self, current: usize, max: usize) {
+        self.hit_points = Some(HitPoints::with_current(current, max));
+    }
+
+    /*
+        Damage to a creature already at 0 HP counts as a failed death
+        save, two on a critical hit, and damage left over after reaching
+        0 HP that is at least the creature's max HP kills it outright.
+     */
+    pub fn take_damage(&mut self, amount: usize, critical: bool) -> Result<Vec<String>, &'static str> {
+        self.can_take_damage()?;
+        let name = self.name.clone();
+        let hit_points = self.hit_points.as_mut().ok_or("HP is not tracked for this creature.")?;
+
+        let was_down = hit_points.is_down();
+        let taken = hit_points.damage(amount);
+        let overflow = amount - taken;
+        let massive_damage = overflow >= hit_points.max();
+
+        let mut updates = vec![format!("{name} takes {taken} damage ({hit_points}).")];
+        if !hit_points.is_down() {
+            return Ok(updates);
+        }
+
+        if !self.uses_death_saves {
+            if !was_down {
+                updates.push(format!("{name} is down!"));
+            }
+            return Ok(updates);
+        }
+
+        if massive_damage {
+            self.downed = Some(Downed::Dead);
+            updates.push(format!("{name} is killed outright by massive damage!"));
+            return Ok(updates);
+        }
+
+        match self.downed {
+            Some(ref mut downed) if was_down => {
+                let result = downed.record_damage(critical);
+                updates.push(self.death_save_update(&result));
+            }
+            _ => {
+                self.downed = Some(Downed::new());
+                updates.push(format!("{name} is down and dying!"));
+            }
+        }
+
+        Ok(updates)
+    }
+
+    // Scales the damage by the creature's resistances, immunities and vulnerabilities first
+    pub fn take_typed_damage(&mut self, amount: usize, damage_type: &str, critical: bool) -> Result<Vec<String>, &'static str> {
+        self.can_take_damage()?;
+
+        let (scaled, applied) = self.damage_modifiers.apply(amount, damage_type);
+        let name = self.name.clone();
+        let mut updates: Vec<String> = applied.iter()
+            .map(|modifier| format!("{name} is {modifier} to {damage_type} damage."))
+            .collect();
+
+        if applied.contains(&DamageModifier::Immunity) {
+            return Ok(updates);
+        }
+
+        updates.append(&mut self.take_damage(scaled, critical)?);
+        Ok(updates)
+    }
+
+    // Checked for a whole group before any member takes damage, so none are left half done
+    pub fn can_take_damage(&self) -> Result<(), &'static str> {
+        match self.hit_points {
+            Some(_) => Ok(()),
+            None => Err("HP is not tracked for this creature.")
+        }
+    }
+
+    pub fn can_heal(&self) -> Result<(), &'static str> {
+        if self.downed.as_ref().is_some_and(|downed| downed.is_dead()) {
+            return Err("Creature is dead.");
+        }
+
+        self.can_take_damage()
+    }
+
+    // Any healing brings a dying or stable creature back, the dead stay dead
+    pub fn heal(&mut self, amount: usize) -> Result<Vec<String>, &'static str> {
+        self.can_heal()?;
+
+        let name = self.name.clone();
+        let hit_points = self.hit_points.as_mut().ok_or("HP is not tracked for this creature.")?;
+
+        let healed = hit_points.heal(amount);
+        let mut updates = vec![format!("{name} regains {healed} HP ({hit_points}).")];
+
+        if healed > 0 && self.downed.take().is_some() {
+            updates.push(format!("{name} is back on their feet!"));
+        }
+
+        Ok(updates)
+    }
+
+    pub fn uses_death_saves(&self) -> bool {
+        self.uses_death_saves
+    }
+
+    pub fn set_uses_death_saves(&mut self, uses_death_saves: bool) {
+        self.uses_death_saves = uses_death_saves;
+    }
+
+    pub fn downed(&self) -> Option<&Downed> {
+        self.downed.as_ref()
+    }
+
+    pub fn is_dying(&self) -> bool {
+        self.downed.as_ref().is_some_and(|downed| downed.is_dying())
+    }
+
+    // roll is the d20 result before any modifiers
+    pub fn record_death_save(&mut self, roll: usize) -> Result<Vec<String>, &'static str> {
+        let downed = self.downed.as_mut().ok_or("Creature is not making death saving throws.")?;
+        let result = downed.record_save(roll)?;
+
+        let name = self.name.clone();
+        let mut updates = vec![format!("{name} rolls {roll} on a death saving throw.")];
+
+        if let DeathSaveResult::Revived = result {
+            self.downed = None;
+            if let Some(ref mut hit_points) = self.hit_points {
+                hit_points.heal(1);
+            }
+        }
+
+        updates.push(self.death_save_update(&result));
+        Ok(updates)
+    }
+
+    fn death_save_update(&self, result: &DeathSaveResult) -> String {
+        let name = self.name();
+        let state = self.downed.as_ref().map(|downed| downed.to_string()).unwrap_or_default();
+
+        match result {
+            DeathSaveResult::Success => format!("{name} succeeds a death save ({state})."),
+            DeathSaveResult::Failure => format!("{name} fails a death save ({state})."),
+            DeathSaveResult::Stabilized => format!("{name} is stable."),
+            DeathSaveResult::Died => format!("{name} has died."),
+            DeathSaveResult::Revived => format!("{name} regains 1 HP and is back on their feet!")
+        }
+    }
+
+    pub fn action_economy(&self) -> &ActionEconomy {
+        &self.action_economy
+    }
+
+    pub fn action_economy_mut(&mut self) -> &mut ActionEconomy {
+        &mut self.action_economy
+    }
+
+    pub fn legendary_actions(&self) -> Option<&LegendaryActions> {
+        self.legendary_actions.as_ref()
+    }
+
+    // Setting 0 actions removes the pool entirely
+    pub fn set_legendary_actions(&mut self, actions: usize) {
+        self.legendary_actions = match actions {
+            0 => None,
+            max => Some(LegendaryActions { max, remaining: max })
+        };
+    }
+
+    // If Ok, returns the remaining and max legendary actions
+    pub fn spend_legendary_action(&mut self) -> Result<(usize, usize), &'static str> {
+        match self.legendary_actions {
+            Some(ref mut legendary_actions) => {
+                if legendary_actions.remaining == 0 {
+                    return Err("No legendary actions remaining.");
+                }
+
+                legendary_actions.remaining -= 1;
+                Ok((legendary_actions.remaining, legendary_actions.max))
+            }
+            None => Err("Creature has no legendary actions.")
+        }
+    }
+
+    pub fn status_effects(&self) -> &Vec<StatusEffect> {
+        &self.status_effects
+    }
+
+    // Removes the first effect with that name, ignoring case. If Ok, returns the update to announce.
+    pub fn remove_status_effect_named(&mut self, effect_name: &str) -> Result<String, &'static str> {
+        let index = self.status_effects.iter()
+            .position(|effect| effect.name().eq_ignore_ascii_case(effect_name))
+            .ok_or("Creature doesn't have that status effect.")?;
+        let effect = self.status_effects.remove(index);
+
+        Ok(format!("Status effect {} has been removed from creature {}.", effect.name(), self.name))
+    }
+
+    // effect_index is 0 based, in the order effects are displayed
+    pub fn set_status_effect_hidden(&mut self, effect_index: usize, hidden: bool) -> Result<(), &'static str> {
+        match self.status_effects.get_mut(effect_index) {
+            Some(effect) => {
+                effect.set_hidden(hidden);
+                Ok(())
+            }
+            None => Err("Status effect index out of range.")
+        }
+    }
+
+    // Name and status effects as players should see them, leaving out hidden effects
+    pub fn public_display(&self) -> String {
+        let mut creature_str = String::from(self.name());
+
+        if let Some(ref hit_points) = self.hit_points.as_ref().filter(|_| self.kind.shows_hit_points_to_players()) {
+            creature_str.push_str(&format!(" [{hit_points}]"));
+        }
+
+        if let Some(ref effects) = self.get_status_effects_display(false) {
+            creature_str.push_str(effects);
+        }
+
+        creature_str
+    }
+
+    // Overrides the rolled initiative to hold a manually chosen position
+    pub fn place_manually(&mut self, initiative: isize) {
+        self.initiative = initiative;
+        self.manually_placed = true;
+    }
+
     fn return_creature_updates(&mut self, effects_to_remove: &Vec<usize>) -> CreatureUpdate {
         let mut updates: Vec<String> = Vec::new();
 
@@ -106,6 +582,71 @@ impl Creature {
         }
     }
 
+    // Rolls each recurring effect due at this point of the turn and applies it to HP
+    fn apply_recurring_effects(&mut self, trigger: ClearType) -> Vec<String> {
+        if self.downed.as_ref().is_some_and(|downed| downed.is_dead()) {
+            return Vec::new();
+        }
+
+        let due: Vec<(String, RecurringEffect)> = self.status_effects.iter()
+            .filter_map(|effect| effect.recurring()
+                .filter(|recurring| recurring.triggers_at(trigger))
+                .map(|recurring| (effect.name().to_string(), recurring.clone())))
+            .collect();
+
+        let mut updates: Vec<String> = Vec::new();
+        for (effect_name, recurring) in due {
+            let amount = recurring.amount().roll();
+            let name = self.name.clone();
+            let result = match recurring.kind() {
+                RecurringKind::Damage(damage_type) => {
+                    let damage_type_str = damage_type.as_deref().map(|damage_type| format!(" {damage_type}")).unwrap_or_default();
+                    updates.push(format!("{effect_name} deals {amount}{damage_type_str} damage to {name}."));
+                    match damage_type {
+                        Some(damage_type) => self.take_typed_damage(amount, damage_type, false),
+                        None => self.take_damage(amount, false)
+                    }
+                }
+                RecurringKind::Healing => {
+                    updates.push(format!("{effect_name} heals {name} for {amount}."));
+                    self.heal(amount)
+                }
+            };
+
+            // Without tracked HP the roll is still announced for the DM to apply
+            if let Ok(mut hit_point_updates) = result {
+                updates.append(&mut hit_point_updates);
+            }
+        }
+
+        updates
+    }
+
+    // Saves are rolled at the end of the creature's turn, ending the effect on a success
+    fn roll_recurring_saves(&mut self) -> Vec<String> {
+        let mut updates: Vec<String> = Vec::new();
+        let name = self.name.clone();
+
+        self.status_effects.retain(|effect| {
+            let Some(save) = effect.recurring().and_then(|recurring| recurring.save()) else {
+                return true;
+            };
+
+            match save.roll(&name, effect.name()) {
+                SaveResult::Success(update) => {
+                    updates.push(update);
+                    false
+                }
+                SaveResult::Failure(update) => {
+                    updates.push(update);
+                    true
+                }
+            }
+        });
+
+        updates
+    }
+
     fn get_status_id(&mut self) -> usize {
         self.next_effect_id += 1;
 
@@ -116,8 +657,12 @@ impl Creature {
         self.status_effects.push(effect);
     }
 
-    fn get_status_effects_display(&self) -> Option<String> {
-        if self.status_effects.is_empty() {
+    fn get_status_effects_display(&self, include_hidden: bool) -> Option<String> {
+        let effects: Vec<&StatusEffect> = self.status_effects.iter()
+            .filter(|effect| include_hidden || !effect.hidden())
+            .collect();
+
+        if effects.is_empty() {
             return None;
         }
 
@@ -126,19 +671,25 @@ impl Creature {
         effects_str.push_str(" [");
 
         
-        for i in 0..(self.status_effects.len()) {
+        for i in 0..(effects.len()) {
 
-            match self.status_effects.get(i) {
+            match effects.get(i) {
                 Some(effect) => {
-                    let effect_name = effect.name();
-                    let turns_left: String;
-                    match effect.turns_left() {
-                        TurnsLeft::Indefinite => turns_left = String::from("∞"),
-                        TurnsLeft::Finite(num) => turns_left = num.to_string()
+                    let mut effect_name = match effect.value() {
+                        Some(value) => format!("{} {value}", effect.name()),
+                        None => effect.name().to_string()
+                    };
+                    if let Some(recurring) = effect.recurring() {
+                        effect_name.push_str(&format!(" ({recurring})"));
                     }
-                    effects_str.push_str(&format!("{effect_name} [{turns_left}]"));
-
-                    if i < self.status_effects.len() - 1 {
+                    let turns_left = match effect.turns_left() {
+                        TurnsLeft::Indefinite => String::from("∞"),
+                        TurnsLeft::Finite(num) => num.to_string()
+                    };
+                    let hidden = if effect.hidden() && include_hidden { " (hidden)" } else { "" };
+                    effects_str.push_str(&format!("{effect_name} [{turns_left}]{hidden}"));
+
+                    if i < effects.len() - 1 {
                         effects_str.push_str(", ");
                     }
                 }
@@ -149,22 +700,65 @@ impl Creature {
             }
         }
 
-        effects_str.push_str("]");
+        effects_str.push(']');
 
         Some(effects_str)
     }
 }
 
 
+impl LegendaryActions {
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl fmt::Display for LegendaryActions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LA {}/{}", self.remaining, self.max)
+    }
+}
+
 impl fmt::Display for Creature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut creature_str = String::new();
 
         creature_str.push_str(self.name());
 
-        match self.get_status_effects_display() {
-            Some(ref effects) => creature_str.push_str(effects),
-            None => ()
+        if let Some(ref hit_points) = self.hit_points {
+            creature_str.push_str(&format!(" [{hit_points}]"));
+        }
+
+        if let Some(ref downed) = self.downed {
+            creature_str.push_str(&format!(" [{downed}]"));
+        }
+
+        if !self.damage_modifiers.is_empty() {
+            creature_str.push_str(&format!(" [{}]", self.damage_modifiers));
+        }
+
+        let passives: Vec<String> = [("PP", self.passive_perception), ("PIns", self.passive_insight), ("PInv", self.passive_investigation)].iter()
+            .filter_map(|(label, passive)| passive.map(|passive| format!("{label} {passive}")))
+            .collect();
+        if !passives.is_empty() {
+            creature_str.push_str(&format!(" [{}]", passives.join(", ")));
+        }
+
+        if let Some(ref legendary_actions) = self.legendary_actions {
+            creature_str.push_str(&format!(" [{legendary_actions}]"));
+        }
+
+        let action_economy = self.action_economy.to_string();
+        if !action_economy.is_empty() {
+            creature_str.push_str(&format!(" [{action_economy}]"));
+        }
+
+        if let Some(ref effects) = self.get_status_effects_display(true) {
+            creature_str.push_str(effects);
         }
 
         write!(f, "{creature_str}")
@@ -0,0 +1,122 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/*
+    What a creature has left to use this round. Everything is
+    restored when the creature's turn begins. Movement is only
+    tracked once the creature has a speed.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct ActionEconomy {
+    reaction: bool,
+    bonus_action: bool,
+    movement: Option<Movement>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Movement {
+    speed: usize,
+    remaining: usize
+}
+
+impl ActionEconomy {
+    pub fn new() -> ActionEconomy {
+        ActionEconomy {
+            reaction: true, bonus_action: true, movement: None
+        }
+    }
+
+    pub fn reaction_available(&self) -> bool {
+        self.reaction
+    }
+
+    pub fn bonus_action_available(&self) -> bool {
+        self.bonus_action
+    }
+
+    pub fn movement(&self) -> Option<&Movement> {
+        self.movement.as_ref()
+    }
+
+    pub fn reset(&mut self) {
+        self.reaction = true;
+        self.bonus_action = true;
+        if let Some(ref mut movement) = self.movement {
+            movement.remaining = movement.speed;
+        }
+    }
+
+    pub fn spend_reaction(&mut self) -> Result<(), &'static str> {
+        if !self.reaction {
+            return Err("Reaction already used this round.");
+        }
+
+        self.reaction = false;
+        Ok(())
+    }
+
+    pub fn spend_bonus_action(&mut self) -> Result<(), &'static str> {
+        if !self.bonus_action {
+            return Err("Bonus action already used this turn.");
+        }
+
+        self.bonus_action = false;
+        Ok(())
+    }
+
+    // A speed of 0 stops tracking movement
+    pub fn set_speed(&mut self, speed: usize) {
+        self.movement = match speed {
+            0 => None,
+            speed => Some(Movement { speed, remaining: speed })
+        };
+    }
+
+    // If Ok, returns the movement remaining
+    pub fn spend_movement(&mut self, distance: usize) -> Result<usize, &'static str> {
+        let movement = self.movement.as_mut().ok_or("Movement is not tracked, set a speed first.")?;
+        if distance > movement.remaining {
+            return Err("Not enough movement remaining.");
+        }
+
+        movement.remaining -= distance;
+        Ok(movement.remaining)
+    }
+}
+
+impl Default for ActionEconomy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Movement {
+    pub fn speed(&self) -> usize {
+        self.speed
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+// Only lists what's been used, so untouched creatures display nothing
+impl fmt::Display for ActionEconomy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut used: Vec<String> = Vec::new();
+
+        if !self.reaction {
+            used.push(String::from("No Reaction"));
+        }
+        if !self.bonus_action {
+            used.push(String::from("No Bonus Action"));
+        }
+        if let Some(ref movement) = self.movement {
+            if movement.remaining < movement.speed {
+                used.push(format!("Move {}/{} ft", movement.remaining, movement.speed));
+            }
+        }
+
+        write!(f, "{}", used.join(", "))
+    }
+}
@@ -0,0 +1,160 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageModifier {
+    Resistance,
+    Immunity,
+    Vulnerability
+}
+
+/*
+    Damage types a creature resists, ignores or takes extra damage from,
+    such as "fire" or "poison". Types are matched ignoring case.
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DamageModifiers {
+    resistances: Vec<String>,
+    immunities: Vec<String>,
+    vulnerabilities: Vec<String>
+}
+
+impl DamageModifiers {
+    pub fn new() -> DamageModifiers {
+        DamageModifiers::default()
+    }
+
+    pub fn resistances(&self) -> &Vec<String> {
+        &self.resistances
+    }
+
+    pub fn immunities(&self) -> &Vec<String> {
+        &self.immunities
+    }
+
+    pub fn vulnerabilities(&self) -> &Vec<String> {
+        &self.vulnerabilities
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resistances.is_empty() && self.immunities.is_empty() && self.vulnerabilities.is_empty()
+    }
+
+    pub fn add(&mut self, modifier: DamageModifier, damage_type: String) {
+        let damage_types = self.damage_types_mut(modifier);
+        if !damage_types.iter().any(|existing| existing.eq_ignore_ascii_case(&damage_type)) {
+            damage_types.push(damage_type);
+        }
+    }
+
+    pub fn set(&mut self, modifier: DamageModifier, damage_types: Vec<String>) {
+        *self.damage_types_mut(modifier) = damage_types;
+    }
+
+    pub fn has(&self, modifier: DamageModifier, damage_type: &str) -> bool {
+        let damage_types = match modifier {
+            DamageModifier::Resistance => &self.resistances,
+            DamageModifier::Immunity => &self.immunities,
+            DamageModifier::Vulnerability => &self.vulnerabilities
+        };
+
+        damage_types.iter().any(|existing| existing.eq_ignore_ascii_case(damage_type.trim()))
+    }
+
+    /*
+        Returns the damage after modifiers and the modifiers that applied.
+        Immunity ignores the damage, resistance halves it rounding down,
+        and vulnerability doubles it after any resistance.
+     */
+    pub fn apply(&self, amount: usize, damage_type: &str) -> (usize, Vec<DamageModifier>) {
+        if self.has(DamageModifier::Immunity, damage_type) {
+            return (0, vec![DamageModifier::Immunity]);
+        }
+
+        let mut amount = amount;
+        let mut applied = Vec::new();
+        if self.has(DamageModifier::Resistance, damage_type) {
+            amount /= 2;
+            applied.push(DamageModifier::Resistance);
+        }
+        if self.has(DamageModifier::Vulnerability, damage_type) {
+            amount *= 2;
+            applied.push(DamageModifier::Vulnerability);
+        }
+
+        (amount, applied)
+    }
+
+    fn damage_types_mut(&mut self, modifier: DamageModifier) -> &mut Vec<String> {
+        match modifier {
+            DamageModifier::Resistance => &mut self.resistances,
+            DamageModifier::Immunity => &mut self.immunities,
+            DamageModifier::Vulnerability => &mut self.vulnerabilities
+        }
+    }
+}
+
+impl fmt::Display for DamageModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifier = match self {
+            DamageModifier::Resistance => "resistant",
+            DamageModifier::Immunity => "immune",
+            DamageModifier::Vulnerability => "vulnerable"
+        };
+
+        write!(f, "{modifier}")
+    }
+}
+
+impl fmt::Display for DamageModifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lists = [("Resist", &self.resistances), ("Immune", &self.immunities), ("Vulnerable", &self.vulnerabilities)];
+        let parts: Vec<String> = lists.iter()
+            .filter(|(_, damage_types)| !damage_types.is_empty())
+            .map(|(label, damage_types)| format!("{label} {}", damage_types.join(", ")))
+            .collect();
+
+        write!(f, "{}", parts.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_match_ignoring_case() {
+        let mut modifiers = DamageModifiers::new();
+        modifiers.add(DamageModifier::Resistance, String::from("Fire"));
+        assert!(modifiers.has(DamageModifier::Resistance, " FIRE "));
+
+        modifiers.add(DamageModifier::Resistance, String::from("fire"));
+        assert_eq!(modifiers.resistances(), &["Fire"]);
+    }
+
+    #[test]
+    fn applies_immunity_resistance_and_vulnerability() {
+        let mut modifiers = DamageModifiers::new();
+        modifiers.add(DamageModifier::Immunity, String::from("poison"));
+        modifiers.add(DamageModifier::Vulnerability, String::from("radiant"));
+        modifiers.add(DamageModifier::Resistance, String::from("fire"));
+        modifiers.add(DamageModifier::Vulnerability, String::from("fire"));
+
+        assert_eq!(modifiers.apply(9, "poison"), (0, vec![DamageModifier::Immunity]));
+        assert_eq!(modifiers.apply(9, "radiant"), (18, vec![DamageModifier::Vulnerability]));
+        assert_eq!(modifiers.apply(9, "cold"), (9, Vec::new()));
+        // Resistance rounds down before vulnerability doubles
+        assert_eq!(modifiers.apply(9, "fire"), (8, vec![DamageModifier::Resistance, DamageModifier::Vulnerability]));
+    }
+
+    #[test]
+    fn displays_each_list() {
+        let mut modifiers = DamageModifiers::new();
+        assert_eq!(modifiers.to_string(), "");
+
+        modifiers.add(DamageModifier::Vulnerability, String::from("fire"));
+        modifiers.add(DamageModifier::Vulnerability, String::from("radiant"));
+        modifiers.add(DamageModifier::Immunity, String::from("poison"));
+        assert_eq!(modifiers.to_string(), "Immune poison | Vulnerable fire, radiant");
+    }
+}
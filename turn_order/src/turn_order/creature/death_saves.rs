@@ -0,0 +1,156 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+const SAVES_NEEDED: usize = 3;
+
+// The state of a creature at 0 HP that makes death saving throws
+#[derive(Serialize, Deserialize)]
+pub enum Downed {
+    Dying { successes: usize, failures: usize },
+    Stable,
+    Dead
+}
+
+pub enum DeathSaveResult {
+    Success,
+    Failure,
+    Stabilized,
+    Died,
+    // A natural 20 brings the creature back with 1 HP
+    Revived
+}
+
+impl Downed {
+    pub fn new() -> Downed {
+        Downed::Dying { successes: 0, failures: 0 }
+    }
+
+    pub fn is_dying(&self) -> bool {
+        matches!(self, Downed::Dying { .. })
+    }
+
+    pub fn is_dead(&self) -> bool {
+        matches!(self, Downed::Dead)
+    }
+
+    /*
+        Applies a d20 roll: 10 or higher succeeds, a natural 1 counts
+        as two failures and a natural 20 revives the creature.
+     */
+    pub fn record_save(&mut self, roll: usize) -> Result<DeathSaveResult, &'static str> {
+        let Downed::Dying { ref mut successes, ref mut failures } = self else {
+            return Err("Creature is not making death saving throws.");
+        };
+
+        let result = match roll {
+            20 => return Ok(DeathSaveResult::Revived),
+            1 => {
+                *failures += 2;
+                DeathSaveResult::Failure
+            }
+            roll if roll >= 10 => {
+                *successes += 1;
+                DeathSaveResult::Success
+            }
+            _ => {
+                *failures += 1;
+                DeathSaveResult::Failure
+            }
+        };
+
+        Ok(self.settle(result))
+    }
+
+    // Taking damage at 0 HP counts as a failure, or two on a critical hit
+    pub fn record_damage(&mut self, critical: bool) -> DeathSaveResult {
+        let added = if critical { 2 } else { 1 };
+
+        match self {
+            Downed::Dying { ref mut failures, .. } => *failures += added,
+            Downed::Stable => *self = Downed::Dying { successes: 0, failures: added },
+            Downed::Dead => return DeathSaveResult::Died
+        }
+
+        self.settle(DeathSaveResult::Failure)
+    }
+
+    fn settle(&mut self, result: DeathSaveResult) -> DeathSaveResult {
+        match self {
+            Downed::Dying { failures, .. } if *failures >= SAVES_NEEDED => {
+                *self = Downed::Dead;
+                DeathSaveResult::Died
+            }
+            Downed::Dying { successes, .. } if *successes >= SAVES_NEEDED => {
+                *self = Downed::Stable;
+                DeathSaveResult::Stabilized
+            }
+            _ => result
+        }
+    }
+}
+
+impl Default for Downed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Downed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Downed::Dying { successes, failures } => write!(f, "Dying S:{successes} F:{failures}"),
+            Downed::Stable => write!(f, "Stable"),
+            Downed::Dead => write!(f, "Dead")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Creature;
+
+    #[test]
+    fn three_successes_stabilize() {
+        let mut downed = Downed::Dying { successes: 2, failures: 2 };
+        assert!(matches!(downed.record_save(10), Ok(DeathSaveResult::Stabilized)));
+        assert!(matches!(downed, Downed::Stable));
+        assert!(downed.record_save(15).is_err());
+    }
+
+    #[test]
+    fn natural_rolls_count_double_or_revive() {
+        let mut downed = Downed::Dying { successes: 0, failures: 1 };
+        assert!(matches!(downed.record_save(1), Ok(DeathSaveResult::Died)));
+        assert!(downed.is_dead());
+
+        let mut downed = Downed::Dying { successes: 0, failures: 2 };
+        assert!(matches!(downed.record_save(20), Ok(DeathSaveResult::Revived)));
+    }
+
+    #[test]
+    fn damage_counts_as_failures() {
+        let mut downed = Downed::Stable;
+        assert!(matches!(downed.record_damage(false), DeathSaveResult::Failure));
+        assert_eq!(downed.to_string(), "Dying S:0 F:1");
+
+        assert!(matches!(downed.record_damage(true), DeathSaveResult::Died));
+        assert!(matches!(downed.record_damage(false), DeathSaveResult::Died));
+    }
+
+    #[test]
+    fn critical_hit_on_a_downed_creature_is_two_failures() {
+        let mut creature = Creature::new(String::from("Aria"), 15);
+        creature.set_max_hit_points(10);
+        creature.set_uses_death_saves(true);
+
+        creature.take_damage(10, false).unwrap();
+        assert_eq!(creature.downed().unwrap().to_string(), "Dying S:0 F:0");
+
+        creature.take_damage(1, true).unwrap();
+        assert_eq!(creature.downed().unwrap().to_string(), "Dying S:0 F:2");
+
+        creature.take_damage(1, false).unwrap();
+        assert!(creature.downed().unwrap().is_dead());
+    }
+}
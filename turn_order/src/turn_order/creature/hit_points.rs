@@ -0,0 +1,57 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct HitPoints {
+    current: usize,
+    max: usize
+}
+
+impl HitPoints {
+    pub fn new(max: usize) -> HitPoints {
+        HitPoints {
+            current: max, max
+        }
+    }
+
+    // Current HP is capped at max
+    pub fn with_current(current: usize, max: usize) -> HitPoints {
+        HitPoints {
+            current: current.min(max), max
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.current == 0
+    }
+
+    // HP can't go below 0, returns the damage actually taken
+    pub fn damage(&mut self, amount: usize) -> usize {
+        let taken = amount.min(self.current);
+        self.current -= taken;
+
+        taken
+    }
+
+    // HP can't go above max, returns the amount actually healed
+    pub fn heal(&mut self, amount: usize) -> usize {
+        let healed = amount.min(self.max - self.current);
+        self.current += healed;
+
+        healed
+    }
+}
+
+impl fmt::Display for HitPoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HP {}/{}", self.current, self.max)
+    }
+}
@@ -0,0 +1,63 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/*
+    Which side of the fight a creature is on. The order of the variants
+    is the order ties in initiative are broken in, players first.
+    Creatures are neutral until tagged, so they aren't counted towards
+    either side when deciding whether the fight is over.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum CreatureKind {
+    PlayerCharacter,
+    Ally,
+    #[default]
+    Neutral,
+    Enemy,
+    Environment
+}
+
+impl CreatureKind {
+    pub const ALL: [CreatureKind; 5] = [
+        CreatureKind::PlayerCharacter,
+        CreatureKind::Ally,
+        CreatureKind::Neutral,
+        CreatureKind::Enemy,
+        CreatureKind::Environment
+    ];
+
+    // Players already know how their own side is doing
+    pub fn shows_hit_points_to_players(&self) -> bool {
+        matches!(self, CreatureKind::PlayerCharacter | CreatureKind::Ally)
+    }
+}
+
+impl fmt::Display for CreatureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            CreatureKind::PlayerCharacter => "PC",
+            CreatureKind::Ally => "Ally",
+            CreatureKind::Neutral => "Neutral",
+            CreatureKind::Enemy => "Enemy",
+            CreatureKind::Environment => "Environment"
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
+impl FromStr for CreatureKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pc" | "player" => Ok(CreatureKind::PlayerCharacter),
+            "ally" => Ok(CreatureKind::Ally),
+            "neutral" => Ok(CreatureKind::Neutral),
+            "enemy" => Ok(CreatureKind::Enemy),
+            "environment" => Ok(CreatureKind::Environment),
+            _ => Err(format!("Unknown creature kind \"{s}\", expected PC, Ally, Neutral, Enemy or Environment."))
+        }
+    }
+}
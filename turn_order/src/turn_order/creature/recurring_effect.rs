@@ -0,0 +1,114 @@
+use crate::turn_order::dice::{self, DiceExpression};
+use super::status_effect::ClearType;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/*
+    Damage or healing a status effect applies every turn, such as
+    ongoing fire damage or a troll's regeneration. A save, if any, is
+    rolled at the end of each of the creature's turns and ends the
+    effect on a success.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecurringEffect {
+    trigger: ClearType,
+    amount: DiceExpression,
+    kind: RecurringKind,
+    save: Option<RecurringSave>
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecurringKind {
+    // Untyped damage skips resistances and the like
+    Damage(Option<String>),
+    Healing
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct RecurringSave {
+    dc: usize,
+    modifier: isize
+}
+
+pub enum SaveResult {
+    Success(String),
+    Failure(String)
+}
+
+impl RecurringEffect {
+    pub fn damage(trigger: ClearType, amount: DiceExpression, damage_type: Option<String>) -> RecurringEffect {
+        RecurringEffect { trigger, amount, kind: RecurringKind::Damage(damage_type), save: None }
+    }
+
+    pub fn healing(trigger: ClearType, amount: DiceExpression) -> RecurringEffect {
+        RecurringEffect { trigger, amount, kind: RecurringKind::Healing, save: None }
+    }
+
+    pub fn with_save(mut self, dc: usize, modifier: isize) -> RecurringEffect {
+        self.save = Some(RecurringSave { dc, modifier });
+        self
+    }
+
+    pub fn trigger(&self) -> ClearType {
+        self.trigger
+    }
+
+    pub fn amount(&self) -> DiceExpression {
+        self.amount
+    }
+
+    pub fn kind(&self) -> &RecurringKind {
+        &self.kind
+    }
+
+    pub fn save(&self) -> Option<RecurringSave> {
+        self.save
+    }
+
+    pub fn triggers_at(&self, trigger: ClearType) -> bool {
+        self.trigger == trigger
+    }
+}
+
+impl RecurringSave {
+    pub fn dc(&self) -> usize {
+        self.dc
+    }
+
+    pub fn modifier(&self) -> isize {
+        self.modifier
+    }
+
+    pub fn roll(&self, creature_name: &str, effect_name: &str) -> SaveResult {
+        let roll = dice::roll_d20();
+        let total = roll.saturating_add_signed(self.modifier);
+        let dc = self.dc;
+
+        match total >= dc {
+            true => SaveResult::Success(format!("{creature_name} succeeds on the DC {dc} save against {effect_name} ({total}) and it ends.")),
+            false => SaveResult::Failure(format!("{creature_name} fails the DC {dc} save against {effect_name} ({total})."))
+        }
+    }
+}
+
+impl fmt::Display for RecurringEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            RecurringKind::Damage(Some(damage_type)) => write!(f, "{} {damage_type}", self.amount)?,
+            RecurringKind::Damage(None) => write!(f, "{} damage", self.amount)?,
+            RecurringKind::Healing => write!(f, "heal {}", self.amount)?
+        }
+
+        let trigger = match self.trigger {
+            ClearType::BeginningOfTurn => "start",
+            ClearType::EndOfTurn => "end"
+        };
+        write!(f, " at {trigger}")?;
+
+        if let Some(save) = self.save {
+            write!(f, ", DC {} ends", save.dc)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,17 +1,53 @@
+use super::recurring_effect::RecurringEffect;
+use core::{fmt, str::FromStr};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
 pub struct StatusEffect {
     id: usize,
     effect_name: String,
     turns_left: TurnsLeft,
-    clear_type: ClearType
+    clear_type: ClearType,
+    // Hidden effects are only shown on the DM's views
+    hidden: bool,
+    // Conditions such as Frightened 2 carry a value alongside their duration
+    value: Option<ConditionValue>,
+    // Damage or healing applied every turn while the effect lasts
+    recurring: Option<RecurringEffect>
 }
 
 pub struct StatusEffectBuilder {
     id: usize,
     effect_name: String,
     turns_left: TurnsLeft,
-    clear_type: Option<ClearType>
+    clear_type: Option<ClearType>,
+    hidden: bool,
+    value: Option<ConditionValue>,
+    recurring: Option<RecurringEffect>
+}
+
+/*
+    A condition's value and how it changes on its own. The effect ends
+    once the value reaches 0.
+ */
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ConditionValue {
+    value: usize,
+    rule: ValueRule
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueRule {
+    // Loses one at the end of each of the creature's turns, like Frightened
+    #[default]
+    DecrementAtEndOfTurn,
+    // Stays until changed or cleared by hand, like Stupefied or Wounded
+    Persistent,
+    // Gains one at the end of each of the creature's turns
+    IncrementAtEndOfTurn
+}
+
+#[derive(Serialize, Deserialize)]
 pub enum TurnsLeft {
     Indefinite,
     Finite(usize)
@@ -22,7 +58,9 @@ pub enum DurationStatus {
     NonExpired
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClearType {
+    #[default]
     BeginningOfTurn,
     EndOfTurn
 }
@@ -30,7 +68,7 @@ pub enum ClearType {
 impl StatusEffect {
     pub fn builder(id: usize, effect_name: String, turns_left: TurnsLeft) -> StatusEffectBuilder {
         StatusEffectBuilder {
-            id, effect_name, turns_left, clear_type: None
+            id, effect_name, turns_left, clear_type: None, hidden: false, value: None, recurring: None
         }
     }
 
@@ -50,6 +88,37 @@ impl StatusEffect {
         &self.turns_left
     }
 
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn value(&self) -> Option<usize> {
+        self.value.map(|condition_value| condition_value.value)
+    }
+
+    pub fn value_rule(&self) -> Option<ValueRule> {
+        self.value.map(|condition_value| condition_value.rule)
+    }
+
+    pub fn recurring(&self) -> Option<&RecurringEffect> {
+        self.recurring.as_ref()
+    }
+
+    // Changes the value by hand, such as Dying going up, Expired once it reaches 0
+    pub fn adjust_value(&mut self, change: isize) -> Result<DurationStatus, &'static str> {
+        let condition_value = self.value.as_mut().ok_or("That status effect doesn't have a value.")?;
+        condition_value.value = condition_value.value.saturating_add_signed(change);
+
+        match condition_value.value {
+            0 => Ok(DurationStatus::Expired),
+            _ => Ok(DurationStatus::NonExpired)
+        }
+    }
+
     /*
         Expectation is that no action will be taken for statuses
         that are cleared on end of turn if beginning of turn, and
@@ -76,6 +145,18 @@ impl StatusEffect {
     }
 
     pub fn end_turn(&mut self) -> DurationStatus {
+        if let Some(ref mut condition_value) = self.value {
+            match condition_value.rule {
+                ValueRule::DecrementAtEndOfTurn => condition_value.value = condition_value.value.saturating_sub(1),
+                ValueRule::IncrementAtEndOfTurn => condition_value.value += 1,
+                ValueRule::Persistent => ()
+            }
+
+            if condition_value.value == 0 {
+                return DurationStatus::Expired;
+            }
+        }
+
         match self.turns_left {
             TurnsLeft::Indefinite => DurationStatus::NonExpired,
             TurnsLeft::Finite(ref mut turns) => {
@@ -102,18 +183,109 @@ impl StatusEffectBuilder {
         self
     }
 
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn value(mut self, value: usize, rule: ValueRule) -> Self {
+        self.value = Some(ConditionValue { value, rule });
+        self
+    }
+
+    pub fn recurring(mut self, recurring: RecurringEffect) -> Self {
+        self.recurring = Some(recurring);
+        self
+    }
+
     pub fn build (self) -> StatusEffect {
         StatusEffect {
             id: self.id,
             effect_name: self.effect_name,
             turns_left: self.turns_left,
-            clear_type: self.clear_type.unwrap_or_default()
+            clear_type: self.clear_type.unwrap_or_default(),
+            hidden: self.hidden,
+            value: self.value,
+            recurring: self.recurring
+        }
+    }
+}
+
+impl ValueRule {
+    pub const ALL: [ValueRule; 3] = [ValueRule::DecrementAtEndOfTurn, ValueRule::Persistent, ValueRule::IncrementAtEndOfTurn];
+}
+
+impl fmt::Display for ValueRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = match self {
+            ValueRule::DecrementAtEndOfTurn => "decrements",
+            ValueRule::Persistent => "persistent",
+            ValueRule::IncrementAtEndOfTurn => "increments"
+        };
+
+        write!(f, "{rule}")
+    }
+}
+
+impl FromStr for ValueRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "decrement" | "decrements" => Ok(ValueRule::DecrementAtEndOfTurn),
+            "persistent" => Ok(ValueRule::Persistent),
+            "increment" | "increments" => Ok(ValueRule::IncrementAtEndOfTurn),
+            _ => Err(format!("Unknown value rule \"{s}\", expected decrement, persistent or increment."))
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valued(value: usize, rule: ValueRule) -> StatusEffect {
+        StatusEffect::builder(0, String::from("Frightened"), TurnsLeft::Indefinite).value(value, rule).build()
+    }
+
+    #[test]
+    fn decrementing_value_ends_the_effect_at_zero() {
+        let mut frightened = valued(2, ValueRule::DecrementAtEndOfTurn);
+        assert!(matches!(frightened.begin_turn(), DurationStatus::NonExpired));
+        assert!(matches!(frightened.end_turn(), DurationStatus::NonExpired));
+        assert_eq!(frightened.value(), Some(1));
+        assert!(matches!(frightened.end_turn(), DurationStatus::Expired));
+    }
+
+    #[test]
+    fn persistent_and_incrementing_values_never_run_out() {
+        let mut stupefied = valued(2, ValueRule::Persistent);
+        let mut dying = valued(1, ValueRule::IncrementAtEndOfTurn);
+        for _ in 0..3 {
+            assert!(matches!(stupefied.end_turn(), DurationStatus::NonExpired));
+            assert!(matches!(dying.end_turn(), DurationStatus::NonExpired));
+        }
+
+        assert_eq!(stupefied.value(), Some(2));
+        assert_eq!(dying.value(), Some(4));
+    }
 
-impl Default for ClearType {
-    fn default() -> Self {
-        ClearType::BeginningOfTurn
+    #[test]
+    fn value_runs_out_before_the_duration() {
+        let mut frightened = StatusEffect::builder(0, String::from("Frightened"), TurnsLeft::Finite(3))
+            .clear_type(ClearType::EndOfTurn)
+            .value(1, ValueRule::DecrementAtEndOfTurn)
+            .build();
+        assert!(matches!(frightened.end_turn(), DurationStatus::Expired));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn adjusting_the_value_by_hand() {
+        let mut stupefied = valued(2, ValueRule::Persistent);
+        assert!(matches!(stupefied.adjust_value(1), Ok(DurationStatus::NonExpired)));
+        assert_eq!(stupefied.value(), Some(3));
+        assert!(matches!(stupefied.adjust_value(-5), Ok(DurationStatus::Expired)));
+
+        let mut blinded = StatusEffect::builder(1, String::from("Blinded"), TurnsLeft::Indefinite).build();
+        assert!(blinded.adjust_value(1).is_err());
+    }
+}
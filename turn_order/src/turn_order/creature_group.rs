@@ -0,0 +1,122 @@
+use super::creature::{Creature, CreatureUpdate, kind::CreatureKind};
+use super::entry::new_id;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/*
+    Several creatures sharing one initiative slot, such as a pack of
+    goblins. Each member keeps its own HP and status effects, which
+    tick individually when the group's turn begins and ends.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct CreatureGroup {
+    #[serde(default = "new_id")]
+    id: u64,
+    name: String,
+    initiative: isize,
+    members: Vec<Creature>,
+    // Whether displays list each member or just the group
+    expanded: bool,
+    manually_placed: bool
+}
+
+impl CreatureGroup {
+    // Members are named after the group and numbered from 1, e.g. "Goblin 1"
+    pub fn new(name: String, initiative: isize, count: usize) -> CreatureGroup {
+        let members = (1..=count)
+            .map(|num| Creature::new(format!("{name} {num}"), initiative))
+            .collect();
+
+        CreatureGroup {
+            id: new_id(), name, initiative, members, expanded: false, manually_placed: false
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn initiative(&self) -> isize {
+        self.initiative
+    }
+
+    // Members share the group's initiative
+    pub fn set_initiative(&mut self, initiative: isize) {
+        self.initiative = initiative;
+        self.manually_placed = false;
+        for member in self.members.iter_mut() {
+            member.set_initiative(initiative);
+        }
+    }
+
+    pub fn members(&self) -> &Vec<Creature> {
+        &self.members
+    }
+
+    pub fn members_mut(&mut self) -> &mut Vec<Creature> {
+        &mut self.members
+    }
+
+    // Members are created together, so the first one speaks for the group
+    pub fn kind(&self) -> CreatureKind {
+        self.members.first().map(|member| member.kind()).unwrap_or_default()
+    }
+
+    pub fn set_kind(&mut self, kind: CreatureKind) {
+        for member in self.members.iter_mut() {
+            member.set_kind(kind);
+        }
+    }
+
+    pub fn expanded(&self) -> bool {
+        self.expanded
+    }
+
+    pub fn set_expanded(&mut self, expanded: bool) {
+        self.expanded = expanded;
+    }
+
+    pub fn manually_placed(&self) -> bool {
+        self.manually_placed
+    }
+
+    pub fn place_manually(&mut self, initiative: isize) {
+        self.initiative = initiative;
+        self.manually_placed = true;
+    }
+
+    pub fn remove_member(&mut self, member_num: usize) -> Result<Creature, &'static str> {
+        if member_num >= self.members.len() {
+            return Err("Group member index out of range.");
+        }
+
+        Ok(self.members.remove(member_num))
+    }
+
+    pub fn begin_turn(&mut self) -> CreatureUpdate {
+        let updates: Vec<String> = self.members.iter_mut()
+            .flat_map(|member| member.begin_turn().into_vec())
+            .collect();
+
+        CreatureUpdate::from_vec(updates)
+    }
+
+    pub fn end_turn(&mut self) -> CreatureUpdate {
+        let updates: Vec<String> = self.members.iter_mut()
+            .flat_map(|member| member.end_turn().into_vec())
+            .collect();
+
+        CreatureUpdate::from_vec(updates)
+    }
+}
+
+// Collapsed summary, expanded member lines are written by the turn order displays
+impl fmt::Display for CreatureGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (group of {})", self.name, self.members.len())
+    }
+}
@@ -0,0 +1,123 @@
+use core::{fmt, str::FromStr};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Rolls a single die with the given number of sides, 1 to sides inclusive
+pub fn roll_die(sides: usize) -> usize {
+    rand::thread_rng().gen_range(1..=sides.max(1))
+}
+
+pub fn roll_d20() -> usize {
+    roll_die(20)
+}
+
+/*
+    A roll such as "2d6+3", "d8" or a flat "5". Totals never go below 0
+    so a penalty can't turn damage into healing.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DiceExpression {
+    count: usize,
+    sides: usize,
+    modifier: isize
+}
+
+impl DiceExpression {
+    pub fn flat(amount: usize) -> DiceExpression {
+        DiceExpression { count: 0, sides: 0, modifier: amount as isize }
+    }
+
+    pub fn roll(&self) -> usize {
+        let rolled: usize = (0..self.count).map(|_| roll_die(self.sides)).sum();
+        rolled.saturating_add_signed(self.modifier)
+    }
+}
+
+impl fmt::Display for DiceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return write!(f, "{}", self.modifier);
+        }
+
+        write!(f, "{}d{}", self.count, self.sides)?;
+        match self.modifier {
+            0 => Ok(()),
+            modifier if modifier > 0 => write!(f, "+{modifier}"),
+            modifier => write!(f, "{modifier}")
+        }
+    }
+}
+
+impl FromStr for DiceExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression: String = s.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        let invalid = || format!("Invalid dice expression \"{s}\", expected something like 2d6+3.");
+
+        // The modifier starts at the last sign that isn't leading the expression
+        let (dice, modifier) = match expression.char_indices().skip(1).filter(|(_, c)| *c == '+' || *c == '-').last() {
+            Some((index, _)) => (&expression[..index], expression[index..].parse::<isize>().map_err(|_| invalid())?),
+            None => (expression.as_str(), 0)
+        };
+
+        match dice.split_once('d') {
+            Some((count, sides)) => {
+                let count = match count {
+                    "" => 1,
+                    count => count.parse::<usize>().map_err(|_| invalid())?
+                };
+                let sides = sides.parse::<usize>().map_err(|_| invalid())?;
+                if sides == 0 {
+                    return Err(invalid());
+                }
+
+                Ok(DiceExpression { count, sides, modifier })
+            }
+            None => {
+                let flat = dice.parse::<isize>().map_err(|_| invalid())?;
+                Ok(DiceExpression { count: 0, sides: 0, modifier: flat + modifier })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expression: &str) -> DiceExpression {
+        expression.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_dice_and_modifiers() {
+        assert_eq!(parse("2d6+3"), DiceExpression { count: 2, sides: 6, modifier: 3 });
+        assert_eq!(parse("d8"), DiceExpression { count: 1, sides: 8, modifier: 0 });
+        assert_eq!(parse(" 1D4 - 1 "), DiceExpression { count: 1, sides: 4, modifier: -1 });
+        assert_eq!(parse("5"), DiceExpression::flat(5));
+        assert_eq!(parse("-2"), DiceExpression { count: 0, sides: 0, modifier: -2 });
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expression in ["", "2d", "d0", "xd6", "2d6+", "2d6+x"] {
+            assert!(expression.parse::<DiceExpression>().is_err(), "\"{expression}\" should not parse");
+        }
+    }
+
+    #[test]
+    fn displays_as_parsed() {
+        for expression in ["2d6+3", "1d8", "1d4-1", "5"] {
+            assert_eq!(parse(expression).to_string(), expression);
+        }
+    }
+
+    #[test]
+    fn rolls_stay_in_range_and_never_go_negative() {
+        for _ in 0..100 {
+            assert!((5..=15).contains(&parse("2d6+3").roll()));
+        }
+        assert_eq!(parse("1-5").roll(), 0);
+    }
+}
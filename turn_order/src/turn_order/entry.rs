@@ -0,0 +1,259 @@
+use super::creature::{Creature, CreatureUpdate, kind::CreatureKind};
+use super::creature_group::CreatureGroup;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+// Lair actions happen on initiative count 20, losing ties
+pub const LAIR_ACTION_INITIATIVE: isize = 20;
+
+/*
+    Tells apart creatures and entries that share a name, such as two
+    creatures both called "Goblin". Random rather than counted, so a
+    creature moved to another encounter can't clash with one there.
+ */
+pub(crate) fn new_id() -> u64 {
+    rand::random()
+}
+
+/*
+    A single slot in the turn order. Most are creatures, but groups
+    share one slot between several creatures, and events such as lair
+    actions also take a turn at a fixed initiative.
+ */
+// Orders only hold a handful of entries, so creatures aren't worth boxing
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize)]
+pub enum TurnEntry {
+    Creature(Creature),
+    Group(CreatureGroup),
+    Event(EventEntry)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventEntry {
+    #[serde(default = "new_id")]
+    id: u64,
+    name: String,
+    initiative: isize,
+    manually_placed: bool
+}
+
+/*
+    Which creatures a command applies to, 0 based. A group entry
+    without a member targets every member of the group.
+ */
+#[derive(Clone, Copy)]
+pub struct Target {
+    index: usize,
+    member: Option<usize>
+}
+
+impl Target {
+    pub fn entry(index: usize) -> Target {
+        Target { index, member: None }
+    }
+
+    pub fn member(index: usize, member: usize) -> Target {
+        Target { index, member: Some(member) }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn member_num(&self) -> Option<usize> {
+        self.member
+    }
+}
+
+impl From<usize> for Target {
+    fn from(index: usize) -> Self {
+        Target::entry(index)
+    }
+}
+
+impl EventEntry {
+    pub fn new(name: String, initiative: isize) -> EventEntry {
+        EventEntry {
+            id: new_id(), name, initiative, manually_placed: false
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn initiative(&self) -> isize {
+        self.initiative
+    }
+}
+
+impl TurnEntry {
+    pub fn id(&self) -> u64 {
+        match self {
+            TurnEntry::Creature(creature) => creature.id(),
+            TurnEntry::Group(group) => group.id(),
+            TurnEntry::Event(event) => event.id
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TurnEntry::Creature(creature) => creature.name(),
+            TurnEntry::Group(group) => group.name(),
+            TurnEntry::Event(event) => event.name()
+        }
+    }
+
+    pub fn initiative(&self) -> isize {
+        match self {
+            TurnEntry::Creature(creature) => creature.initiative(),
+            TurnEntry::Group(group) => group.initiative(),
+            TurnEntry::Event(event) => event.initiative()
+        }
+    }
+
+    // Events have no modifier, groups use their first member's
+    pub fn initiative_modifier(&self) -> isize {
+        self.creatures().first().map(|creature| creature.initiative_modifier()).unwrap_or(0)
+    }
+
+    pub fn extra_initiative_draws(&self) -> usize {
+        self.creatures().first().map(|creature| creature.extra_initiative_draws()).unwrap_or(0)
+    }
+
+    pub fn set_initiative(&mut self, initiative: isize) {
+        match self {
+            TurnEntry::Creature(creature) => creature.set_initiative(initiative),
+            TurnEntry::Group(group) => group.set_initiative(initiative),
+            TurnEntry::Event(event) => {
+                event.initiative = initiative;
+                event.manually_placed = false;
+            }
+        }
+    }
+
+    // Events don't belong to a side
+    pub fn kind(&self) -> Option<CreatureKind> {
+        match self {
+            TurnEntry::Creature(creature) => Some(creature.kind()),
+            TurnEntry::Group(group) => Some(group.kind()),
+            TurnEntry::Event(_) => None
+        }
+    }
+
+    pub fn is_event(&self) -> bool {
+        matches!(self, TurnEntry::Event(_))
+    }
+
+    pub fn as_creature(&self) -> Option<&Creature> {
+        match self {
+            TurnEntry::Creature(creature) => Some(creature),
+            _ => None
+        }
+    }
+
+    pub fn as_creature_mut(&mut self) -> Option<&mut Creature> {
+        match self {
+            TurnEntry::Creature(creature) => Some(creature),
+            _ => None
+        }
+    }
+
+    pub fn as_group(&self) -> Option<&CreatureGroup> {
+        match self {
+            TurnEntry::Group(group) => Some(group),
+            _ => None
+        }
+    }
+
+    pub fn as_group_mut(&mut self) -> Option<&mut CreatureGroup> {
+        match self {
+            TurnEntry::Group(group) => Some(group),
+            _ => None
+        }
+    }
+
+    // Every creature taking this turn, a group's members or a single creature
+    pub fn creatures(&self) -> Vec<&Creature> {
+        match self {
+            TurnEntry::Creature(creature) => vec![creature],
+            TurnEntry::Group(group) => group.members().iter().collect(),
+            TurnEntry::Event(_) => Vec::new()
+        }
+    }
+
+    pub fn creatures_mut(&mut self) -> Vec<&mut Creature> {
+        match self {
+            TurnEntry::Creature(creature) => vec![creature],
+            TurnEntry::Group(group) => group.members_mut().iter_mut().collect(),
+            TurnEntry::Event(_) => Vec::new()
+        }
+    }
+
+    // A group is hidden when all of its members are, events are never hidden
+    pub fn hidden(&self) -> bool {
+        let creatures = self.creatures();
+        !creatures.is_empty() && creatures.iter().all(|creature| creature.hidden())
+    }
+
+    pub fn surprised(&self) -> bool {
+        self.creatures().iter().any(|creature| creature.surprised())
+    }
+
+    pub fn manually_placed(&self) -> bool {
+        match self {
+            TurnEntry::Creature(creature) => creature.manually_placed(),
+            TurnEntry::Group(group) => group.manually_placed(),
+            TurnEntry::Event(event) => event.manually_placed
+        }
+    }
+
+    pub fn place_manually(&mut self, initiative: isize) {
+        match self {
+            TurnEntry::Creature(creature) => creature.place_manually(initiative),
+            TurnEntry::Group(group) => group.place_manually(initiative),
+            TurnEntry::Event(event) => {
+                event.initiative = initiative;
+                event.manually_placed = true;
+            }
+        }
+    }
+
+    pub fn begin_turn(&mut self) -> CreatureUpdate {
+        match self {
+            TurnEntry::Creature(creature) => creature.begin_turn(),
+            TurnEntry::Group(group) => group.begin_turn(),
+            TurnEntry::Event(event) => {
+                let name = event.name();
+                CreatureUpdate::Updates(vec![format!("{name} takes place.")])
+            }
+        }
+    }
+
+    pub fn end_turn(&mut self) -> CreatureUpdate {
+        match self {
+            TurnEntry::Creature(creature) => creature.end_turn(),
+            TurnEntry::Group(group) => group.end_turn(),
+            TurnEntry::Event(_) => CreatureUpdate::NoUpdate
+        }
+    }
+
+    pub fn public_display(&self) -> String {
+        match self {
+            TurnEntry::Creature(creature) => creature.public_display(),
+            TurnEntry::Group(group) => String::from(group.name()),
+            TurnEntry::Event(event) => String::from(event.name())
+        }
+    }
+}
+
+impl fmt::Display for TurnEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnEntry::Creature(creature) => write!(f, "{creature}"),
+            TurnEntry::Group(group) => write!(f, "{group}"),
+            TurnEntry::Event(event) => write!(f, "[EVENT] {}", event.name())
+        }
+    }
+}
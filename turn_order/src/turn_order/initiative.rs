@@ -0,0 +1,351 @@
+pub mod cards;
+
+use super::creature::kind::CreatureKind;
+use super::dice;
+use super::entry::TurnEntry;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+
+/*
+    Decides the order entries take their turns in. TurnOrder sorts with
+    compare whenever entries change, and calls begin_round at the start
+    of every round (including the first) before sorting again.
+ */
+pub trait InitiativeSystem: Send {
+    fn name(&self) -> &'static str;
+
+    // Entries that compare equal keep their current order
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering;
+
+    // Whether events such as lair actions go after creatures they tie with
+    fn events_lose_ties(&self) -> bool {
+        true
+    }
+
+    // Whether remaining ties are broken by creature kind, players first
+    fn kind_breaks_ties(&self) -> bool {
+        true
+    }
+
+    // Rerolls, deals cards and so on, returns the updates to announce
+    fn begin_round(&mut self, _entries: &mut [TurnEntry], _round: usize) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Replaces an entry's initiative with a new draw, such as spending a Benny
+    fn redraw(&mut self, _entry: &mut TurnEntry) -> Result<String, &'static str> {
+        Err("This initiative system doesn't support redraws.")
+    }
+
+    // Whether the entry takes a turn in this pass (1 based) of the round
+    fn acts_in_pass(&self, _entry: &TurnEntry, pass: usize) -> bool {
+        pass == 1
+    }
+
+    // Whether rounds are split into several initiative passes
+    fn has_passes(&self) -> bool {
+        false
+    }
+
+    // Whether initiative is dealt as playing cards, which can be redrawn
+    fn uses_cards(&self) -> bool {
+        false
+    }
+
+    // Whether the creature whose turn it is picks who goes next
+    fn hands_off(&self) -> bool {
+        false
+    }
+
+    // How an entry's place in the order is shown, such as "I:15"
+    fn initiative_display(&self, entry: &TurnEntry) -> String {
+        format!("I:{}", entry.initiative())
+    }
+
+    // Systems that can't be saved are loaded back as Standard
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Standard
+    }
+}
+
+/*
+    A system along with any state it needs mid-combat, such as the
+    card deck or the sides' rolls, so a saved order carries on where
+    it left off when loaded.
+ */
+#[derive(Serialize, Deserialize)]
+pub enum SavedSystem {
+    Standard,
+    Reroll,
+    Side(SideInitiative),
+    Popcorn,
+    Cards(Box<cards::CardInitiative>),
+    Passes
+}
+
+// Highest initiative first, events losing ties, in the same order every round
+#[derive(Default)]
+pub struct Standard;
+
+/*
+    Standard ordering, but everyone rolls again at the start of each
+    round after the first. Extra initiative draws roll more d20s and
+    keep the highest, like advantage.
+ */
+#[derive(Default)]
+pub struct PerRoundReroll;
+
+/*
+    Each side rolls a d20 at the start of the round and the whole side
+    acts together, highest roll first. Within a side creatures go in
+    initiative order. Events always come after every side.
+ */
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct SideInitiative {
+    side_rolls: Vec<(Side, usize)>
+}
+
+/*
+    Popcorn or handoff initiative. The creature whose turn it is picks
+    who goes next from those yet to act, so the order is whatever the
+    table makes it rather than a sort.
+ */
+#[derive(Default)]
+pub struct Popcorn;
+
+/*
+    Shadowrun style initiative passes. Each pass subtracts 10 from
+    everyone's initiative and only those still above 0 act again, so a
+    creature on 23 acts in three passes. Events only act in the first.
+ */
+#[derive(Default)]
+pub struct MultiPass;
+
+const PASS_COST: isize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum Side {
+    Players,
+    Enemies,
+    Neutrals,
+    Events
+}
+
+pub fn default_system() -> Box<dyn InitiativeSystem> {
+    Box::new(Standard)
+}
+
+// Looks up a built in system by the name it displays, ignoring case
+pub fn system_from_name(name: &str) -> Option<Box<dyn InitiativeSystem>> {
+    let system: Box<dyn InitiativeSystem> = match name.trim().to_lowercase().as_str() {
+        "standard" => Box::new(Standard),
+        "reroll" => Box::new(PerRoundReroll),
+        "side" => Box::new(SideInitiative::default()),
+        "popcorn" => Box::new(Popcorn),
+        "cards" => Box::new(cards::CardInitiative::new()),
+        "passes" => Box::new(MultiPass),
+        _ => return None
+    };
+
+    Some(system)
+}
+
+impl SavedSystem {
+    pub fn into_system(self) -> Box<dyn InitiativeSystem> {
+        match self {
+            SavedSystem::Standard => Box::new(Standard),
+            SavedSystem::Reroll => Box::new(PerRoundReroll),
+            SavedSystem::Side(side) => Box::new(side),
+            SavedSystem::Popcorn => Box::new(Popcorn),
+            SavedSystem::Cards(cards) => cards,
+            SavedSystem::Passes => Box::new(MultiPass)
+        }
+    }
+}
+
+// For TurnOrder's serde(with), orders saved before systems were kept load as Standard
+pub(crate) mod saved_system {
+    use super::{InitiativeSystem, SavedSystem};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(system: &Box<dyn InitiativeSystem>, serializer: S) -> Result<S::Ok, S::Error> {
+        system.saved().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<dyn InitiativeSystem>, D::Error> {
+        SavedSystem::deserialize(deserializer).map(SavedSystem::into_system)
+    }
+}
+
+// Ties are left for the turn order to break, see events_lose_ties and kind_breaks_ties
+pub(crate) fn compare_initiative(a: &TurnEntry, b: &TurnEntry) -> Ordering {
+    Reverse(a.initiative()).cmp(&Reverse(b.initiative()))
+}
+
+impl InitiativeSystem for Standard {
+    fn name(&self) -> &'static str {
+        "Standard"
+    }
+
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering {
+        compare_initiative(a, b)
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Standard
+    }
+}
+
+impl InitiativeSystem for PerRoundReroll {
+    fn name(&self) -> &'static str {
+        "Reroll"
+    }
+
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering {
+        compare_initiative(a, b)
+    }
+
+    // The first round uses the initiative everyone rolled when they were added
+    fn begin_round(&mut self, entries: &mut [TurnEntry], round: usize) -> Vec<String> {
+        if round == 1 {
+            return Vec::new();
+        }
+
+        for entry in entries.iter_mut().filter(|entry| !entry.is_event()) {
+            let rolls = 1 + entry.extra_initiative_draws();
+            let best_roll = (0..rolls).map(|_| dice::roll_d20()).max().unwrap_or(1);
+            entry.set_initiative(best_roll as isize + entry.initiative_modifier());
+        }
+
+        vec![String::from("Initiative has been rerolled.")]
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Reroll
+    }
+}
+
+impl Side {
+    fn of(entry: &TurnEntry) -> Side {
+        match entry.kind() {
+            Some(CreatureKind::PlayerCharacter | CreatureKind::Ally) => Side::Players,
+            Some(CreatureKind::Enemy) => Side::Enemies,
+            Some(CreatureKind::Neutral) => Side::Neutrals,
+            Some(CreatureKind::Environment) | None => Side::Events
+        }
+    }
+}
+
+impl SideInitiative {
+    // Sides without a roll yet, such as before combat starts, go in the order of the Side enum
+    fn side_rank(&self, side: Side) -> (Reverse<usize>, usize) {
+        let roll = self.side_rolls.iter()
+            .find(|(rolled_side, _)| *rolled_side == side)
+            .map(|(_, roll)| *roll)
+            .unwrap_or(0);
+
+        (Reverse(roll), side as usize)
+    }
+}
+
+impl InitiativeSystem for SideInitiative {
+    fn name(&self) -> &'static str {
+        "Side"
+    }
+
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering {
+        let (side_a, side_b) = (Side::of(a), Side::of(b));
+        let events_last = (side_a == Side::Events).cmp(&(side_b == Side::Events));
+
+        events_last
+            .then_with(|| self.side_rank(side_a).cmp(&self.side_rank(side_b)))
+            .then_with(|| compare_initiative(a, b))
+    }
+
+    fn begin_round(&mut self, entries: &mut [TurnEntry], _round: usize) -> Vec<String> {
+        let mut sides: Vec<Side> = entries.iter()
+            .map(Side::of)
+            .filter(|side| *side != Side::Events)
+            .collect();
+        sides.sort_by_key(|side| *side as usize);
+        sides.dedup();
+
+        self.side_rolls = sides.into_iter().map(|side| (side, dice::roll_d20())).collect();
+
+        let rolls: Vec<String> = self.side_rolls.iter()
+            .map(|(side, roll)| format!("{side:?} {roll}"))
+            .collect();
+        vec![format!("Side initiative: {}.", rolls.join(", "))]
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Side(self.clone())
+    }
+}
+
+impl InitiativeSystem for Popcorn {
+    fn name(&self) -> &'static str {
+        "Popcorn"
+    }
+
+    fn compare(&self, _a: &TurnEntry, _b: &TurnEntry) -> Ordering {
+        Ordering::Equal
+    }
+
+    // Everyone ties, so the order is only ever changed by hand offs
+    fn events_lose_ties(&self) -> bool {
+        false
+    }
+
+    fn kind_breaks_ties(&self) -> bool {
+        false
+    }
+
+    fn hands_off(&self) -> bool {
+        true
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Popcorn
+    }
+}
+
+impl MultiPass {
+    // How many passes the entry acts in this round, everyone gets at least one
+    fn passes(entry: &TurnEntry) -> usize {
+        match entry.is_event() {
+            true => 1,
+            false => (entry.initiative().max(0) as usize).div_ceil(PASS_COST as usize).max(1)
+        }
+    }
+}
+
+impl InitiativeSystem for MultiPass {
+    fn name(&self) -> &'static str {
+        "Passes"
+    }
+
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering {
+        compare_initiative(a, b)
+    }
+
+    fn acts_in_pass(&self, entry: &TurnEntry, pass: usize) -> bool {
+        pass <= MultiPass::passes(entry)
+    }
+
+    fn has_passes(&self) -> bool {
+        true
+    }
+
+    fn initiative_display(&self, entry: &TurnEntry) -> String {
+        match MultiPass::passes(entry) {
+            1 => format!("I:{}", entry.initiative()),
+            passes => format!("I:{} ({passes} passes)", entry.initiative())
+        }
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Passes
+    }
+}
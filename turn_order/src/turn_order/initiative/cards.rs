@@ -0,0 +1,353 @@
+use core::fmt;
+use super::{compare_initiative, InitiativeSystem, SavedSystem};
+use super::super::entry::TurnEntry;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+const SUITS: [char; 4] = ['♣', '♦', '♥', '♠'];
+const JOKER_VALUE: isize = 60;
+
+/*
+    A playing card. An entry dealt one takes its value as initiative so
+    the usual sorting applies. Higher ranks go first, with suits
+    breaking ties in the order spades, hearts, diamonds, clubs, and
+    jokers beating everything.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Card(isize);
+
+impl Card {
+    // Ranks run from 2 to 14 for an ace, suits from 0 for clubs to 3 for spades
+    fn new(rank: isize, suit: isize) -> Card {
+        Card(rank * 4 + suit)
+    }
+
+    fn joker(number: isize) -> Card {
+        Card(JOKER_VALUE + number)
+    }
+
+    pub fn initiative(&self) -> isize {
+        self.0
+    }
+
+    pub fn is_joker(&self) -> bool {
+        self.0 >= JOKER_VALUE
+    }
+
+    fn full_deck() -> Vec<Card> {
+        let mut deck: Vec<Card> = (2..=14)
+            .flat_map(|rank| (0..4).map(move |suit| Card::new(rank, suit)))
+            .collect();
+        deck.push(Card::joker(0));
+        deck.push(Card::joker(1));
+
+        deck
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_joker() {
+            return write!(f, "Joker");
+        }
+
+        let rank = match self.0 / 4 {
+            14 => String::from("A"),
+            13 => String::from("K"),
+            12 => String::from("Q"),
+            11 => String::from("J"),
+            rank => rank.to_string()
+        };
+
+        write!(f, "{rank}{}", SUITS[(self.0 % 4) as usize])
+    }
+}
+
+/*
+    Savage Worlds style initiative. Every entry is dealt a card each
+    round from a 54 card deck, which is reshuffled after any round a
+    joker was dealt in. Creatures with extra initiative draws, such as
+    Level-Headed, draw that many more cards and keep the best.
+
+    The cards each entry holds are kept by entry id, so a number
+    entered by hand is never mistaken for a card. If the deck runs out
+    partway through a round only the discards are reshuffled, leaving
+    out the cards still in play.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CardInitiative {
+    deck: Vec<Card>,
+    // Entry id and the card it holds this round
+    #[serde(default)]
+    in_play: Vec<(u64, Card)>,
+    #[serde(default)]
+    discards: Vec<Card>,
+    seed: u64,
+    // Each shuffle is seeded from the seed and this count, so a loaded deck deals the same cards
+    shuffles: u64,
+    joker_dealt: bool
+}
+
+impl CardInitiative {
+    pub fn new() -> CardInitiative {
+        CardInitiative::with_seed(rand::random())
+    }
+
+    // The same seed deals the same cards, for replaying a session
+    pub fn with_seed(seed: u64) -> CardInitiative {
+        let mut cards = CardInitiative {
+            deck: Vec::new(), in_play: Vec::new(), discards: Vec::new(), seed, shuffles: 0, joker_dealt: false
+        };
+        cards.shuffle(Card::full_deck());
+
+        cards
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The card an entry was dealt this round, if it was
+    pub fn card(&self, entry: &TurnEntry) -> Option<Card> {
+        self.in_play.iter()
+            .find(|(id, _)| *id == entry.id())
+            .map(|(_, card)| *card)
+    }
+
+    fn shuffle(&mut self, mut cards: Vec<Card>) {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.shuffles));
+        cards.shuffle(&mut rng);
+        self.deck = cards;
+        self.shuffles += 1;
+    }
+
+    // Every card goes back in, including the ones that were in play
+    fn reshuffle_all(&mut self) {
+        self.in_play.clear();
+        self.discards.clear();
+        self.shuffle(Card::full_deck());
+        self.joker_dealt = false;
+    }
+
+    /*
+        Reshuffles the discards if the deck runs out partway through a
+        round. Only with more entries than cards is there nothing left
+        to reshuffle, and a fresh deck is started instead.
+     */
+    fn draw(&mut self) -> Card {
+        if self.deck.is_empty() {
+            let discards = std::mem::take(&mut self.discards);
+            match discards.is_empty() {
+                true => self.shuffle(Card::full_deck()),
+                false => self.shuffle(discards)
+            }
+        }
+
+        let card = self.deck.pop().unwrap_or(Card::joker(0));
+        self.joker_dealt |= card.is_joker();
+
+        card
+    }
+
+    // The cards not kept are discarded
+    fn draw_best(&mut self, draws: usize) -> Card {
+        let mut drawn: Vec<Card> = (0..draws.max(1)).map(|_| self.draw()).collect();
+        drawn.sort();
+        let best = drawn.pop().unwrap_or(Card::joker(0));
+        self.discards.append(&mut drawn);
+
+        best
+    }
+}
+
+impl Default for CardInitiative {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InitiativeSystem for CardInitiative {
+    fn name(&self) -> &'static str {
+        "Cards"
+    }
+
+    /*
+        Entries dealt a card go ahead of ones added since the deal,
+        which have no card yet. Events go on the card they tie with.
+     */
+    fn compare(&self, a: &TurnEntry, b: &TurnEntry) -> Ordering {
+        if self.in_play.is_empty() {
+            return compare_initiative(a, b);
+        }
+
+        let dealt = |entry: &TurnEntry| entry.is_event() || self.card(entry).is_some();
+        dealt(b).cmp(&dealt(a)).then_with(|| compare_initiative(a, b))
+    }
+
+    // Suits already settle ties
+    fn kind_breaks_ties(&self) -> bool {
+        false
+    }
+
+    fn begin_round(&mut self, entries: &mut [TurnEntry], _round: usize) -> Vec<String> {
+        let mut updates = Vec::new();
+        if self.joker_dealt {
+            self.reshuffle_all();
+            updates.push(String::from("The deck has been reshuffled."));
+        }
+        else {
+            let in_play = std::mem::take(&mut self.in_play);
+            self.discards.extend(in_play.into_iter().map(|(_, card)| card));
+        }
+
+        // Events such as lair actions keep their initiative and go on the card it ties with
+        for entry in entries.iter_mut().filter(|entry| !entry.is_event()) {
+            let card = self.draw_best(1 + entry.extra_initiative_draws());
+            entry.set_initiative(card.initiative());
+            self.in_play.push((entry.id(), card));
+            if card.is_joker() {
+                updates.push(format!("{} drew a Joker! +2 to Trait and damage rolls this round.", entry.name()));
+            }
+        }
+
+        updates
+    }
+
+    // Keeps the better of the current card and a new one
+    fn redraw(&mut self, entry: &mut TurnEntry) -> Result<String, &'static str> {
+        if entry.is_event() {
+            return Err("Events aren't dealt cards.");
+        }
+
+        let held = self.in_play.iter()
+            .position(|(id, _)| *id == entry.id())
+            .ok_or("That entry hasn't been dealt a card.")?;
+        let current = self.in_play[held].1;
+        let card = self.draw();
+        let (kept, discarded) = match card > current {
+            true => (card, current),
+            false => (current, card)
+        };
+        self.in_play[held].1 = kept;
+        self.discards.push(discarded);
+        entry.set_initiative(kept.initiative());
+
+        Ok(format!("{} drew {card} and keeps {kept}.", entry.name()))
+    }
+
+    fn initiative_display(&self, entry: &TurnEntry) -> String {
+        if entry.is_event() {
+            return format!("I:{}", entry.initiative());
+        }
+
+        match self.card(entry) {
+            Some(card) => format!("[{card}]"),
+            None => String::from("[No card]")
+        }
+    }
+
+    fn uses_cards(&self) -> bool {
+        true
+    }
+
+    fn saved(&self) -> SavedSystem {
+        SavedSystem::Cards(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::{entry::LAIR_ACTION_INITIATIVE, TurnOrder};
+
+    fn dealt_order(seed: u64) -> TurnOrder {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 0);
+        turn_order.add_creature(String::from("Brom"), 0);
+        turn_order.add_creature(String::from("Cade"), 0);
+        turn_order.add_lair_action();
+        turn_order.set_initiative_system(Box::new(CardInitiative::with_seed(seed)));
+        turn_order.start_combat().unwrap();
+
+        turn_order
+    }
+
+    fn dealt(turn_order: &TurnOrder) -> Vec<(String, isize)> {
+        turn_order.entries().iter().map(|entry| (entry.name().to_string(), entry.initiative())).collect()
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_order() {
+        let (first, second) = (dealt_order(42), dealt_order(42));
+        assert_eq!(dealt(&first), dealt(&second));
+        assert_ne!(dealt(&first), dealt(&dealt_order(43)));
+    }
+
+    #[test]
+    fn events_are_not_dealt_cards() {
+        let mut turn_order = dealt_order(7);
+        let lair = turn_order.entries().iter().position(|entry| entry.is_event()).unwrap();
+        assert_eq!(turn_order.entries()[lair].initiative(), LAIR_ACTION_INITIATIVE);
+        assert_eq!(turn_order.initiative_system().initiative_display(&turn_order.entries()[lair]), "I:20");
+        assert!(turn_order.redraw_initiative(lair).is_err());
+    }
+
+    #[test]
+    fn saved_deck_deals_the_same_cards() {
+        let mut cards = CardInitiative::with_seed(3);
+        for _ in 0..60 {
+            let card = cards.draw();
+            cards.discards.push(card);
+        }
+
+        let mut restored: CardInitiative = serde_json::from_str(&serde_json::to_string(&cards).unwrap()).unwrap();
+        let draws: Vec<Card> = (0..60).map(|_| cards.draw()).collect();
+        let restored_draws: Vec<Card> = (0..60).map(|_| restored.draw()).collect();
+        assert_eq!(draws, restored_draws);
+    }
+
+    #[test]
+    fn initiative_entered_by_hand_is_not_a_card() {
+        let mut turn_order = dealt_order(7);
+        turn_order.add_creature(String::from("Dara"), 15);
+
+        let dara = turn_order.entries().last().unwrap();
+        assert_eq!(dara.name(), "Dara");
+        assert_eq!(turn_order.initiative_system().initiative_display(dara), "[No card]");
+        assert!(turn_order.redraw_initiative(turn_order.entries().len() - 1).is_err());
+    }
+
+    // Redraws and extra draws go through the deck several times over within one round
+    #[test]
+    fn cards_in_play_are_never_dealt_twice() {
+        let mut turn_order = TurnOrder::new();
+        for index in 0..12 {
+            turn_order.add_creature(format!("Creature {index}"), 0);
+        }
+        for index in 0..6 {
+            turn_order.set_extra_initiative_draws(index, 1).unwrap();
+        }
+        turn_order.set_initiative_system(Box::new(CardInitiative::with_seed(11)));
+
+        // Jokers both show as "Joker", so the cards are compared by value
+        let cards_in_play = |turn_order: &TurnOrder| -> Vec<isize> {
+            let mut cards: Vec<isize> = turn_order.entries().iter().map(|entry| entry.initiative()).collect();
+            cards.sort();
+            cards
+        };
+
+        turn_order.start_combat().unwrap();
+        for round in 0..3 {
+            for redraw in 0..40 {
+                turn_order.redraw_initiative(redraw % 12).unwrap();
+                let mut cards = cards_in_play(&turn_order);
+                cards.dedup();
+                assert_eq!(cards.len(), 12, "a card was dealt twice in round {round}: {cards:?}");
+            }
+            while turn_order.current_turn() != 0 || turn_order.round() == round + 1 {
+                turn_order.next_turn().unwrap();
+            }
+        }
+    }
+}
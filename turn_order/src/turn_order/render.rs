@@ -0,0 +1,231 @@
+use super::{creature::{status_effect::TurnsLeft, Creature}, entry::TurnEntry, TurnOrder};
+
+/*
+    The turn order written out for campaign notes. Without hidden
+    entries it matches what players see: hidden creatures and effects
+    are left out, the rest are numbered without gaps, and HP is only
+    shown for the players' side.
+ */
+struct Row {
+    number: String,
+    initiative: String,
+    name: String,
+    hit_points: String,
+    effects: String,
+    current: bool,
+    member: bool
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; background: #fafafa; color: #222; }
+table { border-collapse: collapse; }
+th, td { padding: 0.4em 0.8em; border-bottom: 1px solid #ccc; text-align: left; }
+th { background: #333; color: #fff; }
+tr.member td:nth-child(3) { padding-left: 2em; }
+tr.current { background: #ffe58a; font-weight: bold; }";
+
+impl TurnOrder {
+    pub fn to_markdown(&self, include_hidden: bool) -> String {
+        let mut markdown = String::from("# Turn Order\n\n");
+        if self.combat_started {
+            markdown.push_str(&format!("{}\n\n", self.round_display()));
+        }
+
+        markdown.push_str("| # | Initiative | Name | HP | Effects |\n");
+        markdown.push_str("|---|---|---|---|---|\n");
+        for row in self.rows(include_hidden) {
+            let cells = [&row.number, &row.initiative, &row.name, &row.hit_points, &row.effects];
+            let cells: Vec<String> = cells.iter()
+                .map(|cell| escape_markdown(cell))
+                .map(|cell| match row.current && !cell.is_empty() {
+                    true => format!("**{cell}**"),
+                    false => cell
+                })
+                .collect();
+            markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+
+        markdown
+    }
+
+    // A standalone page with the current turn highlighted
+    pub fn to_html(&self, include_hidden: bool) -> String {
+        let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Turn Order</title>\n");
+        html.push_str(&format!("<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<h1>Turn Order</h1>\n"));
+        if self.combat_started {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(&self.round_display())));
+        }
+
+        html.push_str("<table>\n<tr><th>#</th><th>Initiative</th><th>Name</th><th>HP</th><th>Effects</th></tr>\n");
+        for row in self.rows(include_hidden) {
+            let classes: Vec<&str> = [(row.current, "current"), (row.member, "member")].iter()
+                .filter_map(|(is_set, class)| is_set.then_some(*class))
+                .collect();
+            let class = match classes.is_empty() {
+                true => String::new(),
+                false => format!(" class=\"{}\"", classes.join(" "))
+            };
+
+            let cells: String = [&row.number, &row.initiative, &row.name, &row.hit_points, &row.effects].iter()
+                .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                .collect();
+            html.push_str(&format!("<tr{class}>{cells}</tr>\n"));
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    fn rows(&self, include_hidden: bool) -> Vec<Row> {
+        let visible = self.entries.iter().enumerate()
+            .filter(|(_, entry)| include_hidden || !entry.hidden());
+
+        let mut rows: Vec<Row> = Vec::new();
+        for (entry_num, (index, entry)) in (1..).zip(visible) {
+            let current = self.combat_started && index == self.current_turn;
+            let initiative = self.initiative_system.initiative_display(entry);
+
+            match entry {
+                TurnEntry::Creature(creature) => rows.push(creature_row(creature, entry_num.to_string(), initiative, current, false, include_hidden)),
+                TurnEntry::Event(event) => rows.push(Row {
+                    number: entry_num.to_string(),
+                    initiative,
+                    name: event.name().to_string(),
+                    hit_points: String::new(),
+                    effects: String::new(),
+                    current,
+                    member: false
+                }),
+                TurnEntry::Group(group) => {
+                    rows.push(Row {
+                        number: entry_num.to_string(),
+                        initiative,
+                        name: group.name().to_string(),
+                        hit_points: String::new(),
+                        effects: String::new(),
+                        current,
+                        member: false
+                    });
+
+                    let members = group.members().iter().filter(|member| include_hidden || !member.hidden());
+                    for (member_num, member) in (1..).zip(members) {
+                        rows.push(creature_row(member, format!("{entry_num}.{member_num}"), String::new(), current, true, include_hidden));
+                    }
+                }
+            }
+        }
+
+        rows
+    }
+}
+
+fn creature_row(creature: &Creature, number: String, initiative: String, current: bool, member: bool, include_hidden: bool) -> Row {
+    let hidden = if creature.hidden() { " (hidden)" } else { "" };
+    let shows_hit_points = include_hidden || creature.kind().shows_hit_points_to_players();
+
+    let mut hit_points = match creature.hit_points() {
+        Some(hit_points) if shows_hit_points => format!("{}/{}", hit_points.current(), hit_points.max()),
+        _ => String::new()
+    };
+    if let Some(downed) = creature.downed() {
+        hit_points.push_str(&format!(" ({downed})"));
+    }
+
+    let effects: Vec<String> = creature.status_effects().iter()
+        .filter(|effect| include_hidden || !effect.hidden())
+        .map(|effect| {
+            let name = match effect.value() {
+                Some(value) => format!("{} {value}", effect.name()),
+                None => effect.name().to_string()
+            };
+            match effect.turns_left() {
+                TurnsLeft::Finite(1) => format!("{name} (1 turn)"),
+                TurnsLeft::Finite(turns) => format!("{name} ({turns} turns)"),
+                TurnsLeft::Indefinite => name
+            }
+        })
+        .collect();
+
+    Row {
+        number,
+        initiative,
+        name: format!("{}{hidden}", creature.name()),
+        hit_points: hit_points.trim().to_string(),
+        effects: effects.join(", "),
+        current,
+        member
+    }
+}
+
+// Pipes would end the table cell early
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::creature::kind::CreatureKind;
+
+    #[test]
+    fn player_markdown_leaves_out_what_players_cant_see() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("Goblin | Boss"), 14);
+        turn_order.add_creature_hidden(String::from("Assassin"), 20);
+        turn_order.set_max_hit_points(1, 30).unwrap();
+        turn_order.set_max_hit_points(2, 7).unwrap();
+        turn_order.add_status_effect(2, String::from("Prone")).unwrap();
+        turn_order.add_status_effect_hidden(2, String::from("Cursed")).unwrap();
+        turn_order.set_kind(1, CreatureKind::PlayerCharacter).unwrap();
+        turn_order.start_combat().unwrap();
+
+        assert_eq!(turn_order.to_markdown(false), "\
+# Turn Order
+
+Round 1
+
+| # | Initiative | Name | HP | Effects |
+|---|---|---|---|---|
+| 1 | I:18 | Aria | 30/30 |  |
+| 2 | I:14 | Goblin \\| Boss |  | Prone |
+");
+    }
+
+    #[test]
+    fn dm_markdown_shows_everything() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature_hidden(String::from("Assassin"), 20);
+        turn_order.add_creature(String::from("Goblin"), 14);
+        turn_order.set_max_hit_points(1, 7).unwrap();
+        turn_order.add_status_effect(1, String::from("Prone")).unwrap();
+        turn_order.add_status_effect_hidden(1, String::from("Cursed")).unwrap();
+        turn_order.start_combat().unwrap();
+
+        let markdown = turn_order.to_markdown(true);
+        assert!(markdown.contains("| **1** | **I:20** | **Assassin (hidden)** |"), "{markdown}");
+        assert!(markdown.contains("| 2 | I:14 | Goblin | 7/7 | Prone, Cursed |"), "{markdown}");
+    }
+
+    #[test]
+    fn html_highlights_the_current_turn_and_escapes_names() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature_hidden(String::from("Assassin"), 20);
+        turn_order.add_creature(String::from("Aria"), 18);
+        turn_order.add_creature(String::from("<Mimic>"), 2);
+        turn_order.start_combat().unwrap();
+        turn_order.next_turn().unwrap();
+
+        let html = turn_order.to_html(false);
+        assert!(html.contains("<tr class=\"current\"><td>1</td><td>I:18</td><td>Aria</td>"), "{html}");
+        assert!(html.contains("<td>&lt;Mimic&gt;</td>"), "{html}");
+        assert!(!html.contains("Assassin"));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::storage;
+use super::creature::Creature;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Scope, AST, INT};
+use std::{fs, path::Path, sync::{Arc, Mutex, MutexGuard}};
+
+const SCRIPTS_DIR: &str = "scripts";
+
+/*
+    Homebrew rules written in Rhai. A script defines any of these
+    functions, which are called as the tracker runs:
+
+        fn on_turn_start(creature)
+        fn on_turn_end(creature)
+        fn on_round_start(round)
+        fn on_effect_added(creature, effect)
+        fn on_damage_taken(creature, amount)
+
+    Creatures are passed and looked up by name. Scripts read the tracker
+    with round(), creatures(), hit_points(name), max_hit_points(name),
+    has_effect(name, effect) and kind(name), and change it with
+    damage(name, amount), heal(name, amount), add_effect(name, effect),
+    remove_effect(name, effect) and message(text). Changes are applied
+    once the hook returns and don't set off any hooks themselves.
+
+    Scripts can't touch the filesystem, and runaway loops are cut off by
+    the engine's operation limit. Errors become messages for the DM.
+ */
+pub struct ScriptHost {
+    engine: Engine,
+    scripts: Vec<Script>,
+    state: Arc<Mutex<HookState>>
+}
+
+struct Script {
+    name: String,
+    ast: AST
+}
+
+pub enum Hook {
+    TurnStart(String),
+    TurnEnd(String),
+    RoundStart(usize),
+    EffectAdded(String, String),
+    DamageTaken(String, usize)
+}
+
+pub enum ScriptCommand {
+    Damage(String, usize),
+    Heal(String, usize),
+    AddEffect(String, String),
+    RemoveEffect(String, String),
+    Message(String)
+}
+
+// What a hook can see of the tracker, and what it asked to change
+#[derive(Default)]
+struct HookState {
+    round: usize,
+    creatures: Vec<ScriptCreature>,
+    commands: Vec<ScriptCommand>
+}
+
+struct ScriptCreature {
+    name: String,
+    hit_points: Option<(usize, usize)>,
+    effects: Vec<String>,
+    kind: String
+}
+
+impl ScriptHost {
+    pub fn new() -> ScriptHost {
+        let state = Arc::new(Mutex::new(HookState::default()));
+        let mut engine = Engine::new();
+
+        engine.set_max_operations(100_000);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(10_000);
+        engine.set_max_array_size(1_000);
+        engine.set_max_map_size(1_000);
+        engine.disable_symbol("eval");
+
+        register_api(&mut engine, &state);
+
+        ScriptHost { engine, scripts: Vec::new(), state }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    pub fn script_names(&self) -> impl Iterator<Item = &str> {
+        self.scripts.iter().map(|script| script.name.as_str())
+    }
+
+    // A script with the same name replaces the old one, so edited scripts can be reloaded
+    pub fn add(&mut self, name: String, source: &str) -> Result<(), String> {
+        let ast = self.engine.compile(source).map_err(|e| format!("Could not compile script {name}: {e}"))?;
+
+        self.scripts.retain(|script| script.name != name);
+        self.scripts.push(Script { name, ast });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), &'static str> {
+        let index = self.scripts.iter().position(|script| script.name == name).ok_or("No script with that name.")?;
+        self.scripts.remove(index);
+        Ok(())
+    }
+
+    // Loads every script in the data directory's scripts folder, if there is one
+    pub fn load(&mut self) -> Result<usize, String> {
+        let dir = storage::data_file(SCRIPTS_DIR)?;
+        match dir.is_dir() {
+            true => self.import(&dir),
+            false => Ok(0)
+        }
+    }
+
+    // Loads a .rhai file, or every .rhai file in a folder. Returns how many were loaded.
+    pub fn import(&mut self, path: &Path) -> Result<usize, String> {
+        if !path.is_dir() {
+            return self.import_file(path).map(|_| 1);
+        }
+
+        let entries = fs::read_dir(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let mut count = 0;
+        for entry in entries.flatten() {
+            let file = entry.path();
+            if file.extension().is_some_and(|extension| extension == "rhai") {
+                self.import_file(&file)?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    fn import_file(&mut self, path: &Path) -> Result<(), String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+        self.add(name, &source)
+    }
+
+    /*
+        Calls the hook in every script that defines it, returning what
+        the scripts asked for in the order they asked. Errors are turned
+        into messages so one broken script doesn't stop the others.
+     */
+    pub fn run<'a>(&self, hook: &Hook, round: usize, creatures: impl Iterator<Item = &'a Creature>) -> Vec<ScriptCommand> {
+        if self.scripts.is_empty() {
+            return Vec::new();
+        }
+
+        *lock(&self.state) = HookState {
+            round,
+            creatures: creatures.map(ScriptCreature::from).collect(),
+            commands: Vec::new()
+        };
+
+        let function_name = hook.function_name();
+        let args = hook.args();
+        for script in &self.scripts {
+            let defines_hook = script.ast.iter_functions()
+                .any(|function| function.name == function_name && function.params.len() == args.len());
+            if !defines_hook {
+                continue;
+            }
+
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, function_name, args.clone());
+            if let Err(e) = result {
+                let update = format!("Script error in {} ({function_name}): {e}", script.name);
+                lock(&self.state).commands.push(ScriptCommand::Message(update));
+            }
+        }
+
+        std::mem::take(&mut lock(&self.state).commands)
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        ScriptHost::new()
+    }
+}
+
+impl Hook {
+    fn function_name(&self) -> &'static str {
+        match self {
+            Hook::TurnStart(_) => "on_turn_start",
+            Hook::TurnEnd(_) => "on_turn_end",
+            Hook::RoundStart(_) => "on_round_start",
+            Hook::EffectAdded(_, _) => "on_effect_added",
+            Hook::DamageTaken(_, _) => "on_damage_taken"
+        }
+    }
+
+    fn args(&self) -> Vec<Dynamic> {
+        match self {
+            Hook::TurnStart(name) | Hook::TurnEnd(name) => vec![name.clone().into()],
+            Hook::RoundStart(round) => vec![Dynamic::from(*round as INT)],
+            Hook::EffectAdded(name, effect) => vec![name.clone().into(), effect.clone().into()],
+            Hook::DamageTaken(name, amount) => vec![name.clone().into(), Dynamic::from(*amount as INT)]
+        }
+    }
+}
+
+impl From<&Creature> for ScriptCreature {
+    fn from(creature: &Creature) -> Self {
+        ScriptCreature {
+            name: creature.name().to_string(),
+            hit_points: creature.hit_points().map(|hit_points| (hit_points.current(), hit_points.max())),
+            effects: creature.status_effects().iter().map(|effect| effect.name().to_string()).collect(),
+            kind: creature.kind().to_string()
+        }
+    }
+}
+
+// A script that panicked mid-hook shouldn't take the tracker down with it
+fn lock(state: &Mutex<HookState>) -> MutexGuard<'_, HookState> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn register_api(engine: &mut Engine, state: &Arc<Mutex<HookState>>) {
+    let find = |state: &HookState, name: &str| -> Option<usize> {
+        state.creatures.iter().position(|creature| creature.name.eq_ignore_ascii_case(name))
+    };
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("round", move || lock(&hook_state).round as INT);
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("creatures", move || -> Array {
+        lock(&hook_state).creatures.iter().map(|creature| creature.name.clone().into()).collect()
+    });
+
+    // -1 when the creature isn't found or its HP isn't tracked
+    let hook_state = Arc::clone(state);
+    engine.register_fn("hit_points", move |name: &str| -> INT {
+        let state = lock(&hook_state);
+        find(&state, name).and_then(|index| state.creatures[index].hit_points).map_or(-1, |(current, _)| current as INT)
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("max_hit_points", move |name: &str| -> INT {
+        let state = lock(&hook_state);
+        find(&state, name).and_then(|index| state.creatures[index].hit_points).map_or(-1, |(_, max)| max as INT)
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("has_effect", move |name: &str, effect: &str| -> bool {
+        let state = lock(&hook_state);
+        find(&state, name).is_some_and(|index| state.creatures[index].effects.iter().any(|other| other.eq_ignore_ascii_case(effect)))
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("kind", move |name: &str| -> String {
+        let state = lock(&hook_state);
+        find(&state, name).map(|index| state.creatures[index].kind.clone()).unwrap_or_default()
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("damage", move |name: &str, amount: INT| {
+        lock(&hook_state).commands.push(ScriptCommand::Damage(name.to_string(), amount.max(0) as usize));
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("heal", move |name: &str, amount: INT| {
+        lock(&hook_state).commands.push(ScriptCommand::Heal(name.to_string(), amount.max(0) as usize));
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("add_effect", move |name: &str, effect: &str| {
+        lock(&hook_state).commands.push(ScriptCommand::AddEffect(name.to_string(), effect.to_string()));
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("remove_effect", move |name: &str, effect: &str| {
+        lock(&hook_state).commands.push(ScriptCommand::RemoveEffect(name.to_string(), effect.to_string()));
+    });
+
+    let hook_state = Arc::clone(state);
+    engine.register_fn("message", move |text: &str| {
+        lock(&hook_state).commands.push(ScriptCommand::Message(text.to_string()));
+    });
+
+    // print() is treated the same as message()
+    let hook_state = Arc::clone(state);
+    engine.on_print(move |text| {
+        lock(&hook_state).commands.push(ScriptCommand::Message(text.to_string()));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(commands: &[ScriptCommand]) -> Vec<&str> {
+        commands.iter()
+            .filter_map(|command| match command {
+                ScriptCommand::Message(text) => Some(text.as_str()),
+                _ => None
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hooks_read_the_tracker_and_queue_changes() {
+        let mut scripts = ScriptHost::new();
+        scripts.add(String::from("bloodied"), r#"
+            fn on_damage_taken(creature, amount) {
+                if hit_points(creature) * 2 < max_hit_points(creature) {
+                    add_effect(creature, "Bloodied");
+                }
+                message(`${creature} took ${amount} in round ${round()}`);
+            }
+        "#).unwrap();
+
+        let mut ogre = Creature::new(String::from("Ogre"), 8);
+        ogre.set_hit_points(20, 59);
+        let commands = scripts.run(&Hook::DamageTaken(String::from("Ogre"), 12), 3, [&ogre].into_iter());
+        assert!(matches!(&commands[0], ScriptCommand::AddEffect(name, effect) if name == "Ogre" && effect == "Bloodied"));
+        assert_eq!(messages(&commands), ["Ogre took 12 in round 3"]);
+    }
+
+    #[test]
+    fn only_scripts_defining_the_hook_run() {
+        let mut scripts = ScriptHost::new();
+        scripts.add(String::from("start"), r#"fn on_turn_start(creature) { damage(creature, 2); }"#).unwrap();
+
+        let ogre = Creature::new(String::from("Ogre"), 8);
+        assert!(scripts.run(&Hook::TurnEnd(String::from("Ogre")), 1, [&ogre].into_iter()).is_empty());
+        let commands = scripts.run(&Hook::TurnStart(String::from("Ogre")), 1, [&ogre].into_iter());
+        assert!(matches!(&commands[..], [ScriptCommand::Damage(name, 2)] if name == "Ogre"));
+    }
+
+    #[test]
+    fn broken_scripts_become_messages() {
+        let mut scripts = ScriptHost::new();
+        assert!(scripts.add(String::from("typo"), "fn on_round_start(round) {").is_err());
+        scripts.add(String::from("forever"), "fn on_round_start(round) { loop { } }").unwrap();
+        scripts.add(String::from("unknown"), "fn on_round_start(round) { explode(); }").unwrap();
+
+        let commands = scripts.run(&Hook::RoundStart(1), 1, std::iter::empty());
+        let messages = messages(&commands);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("Script error in forever (on_round_start)"));
+        assert!(messages[1].starts_with("Script error in unknown (on_round_start)"));
+    }
+}
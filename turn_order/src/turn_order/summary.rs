@@ -0,0 +1,167 @@
+use super::creature::kind::CreatureKind;
+use serde::{Deserialize, Serialize};
+
+/*
+    What happened during a fight, collected as it goes and handed out
+    when combat ends. Creatures are listed in the order they first
+    did something, keyed by their id so two creatures with the same
+    name are kept apart, and shown by their latest name.
+ */
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CombatSummary {
+    rounds: usize,
+    creatures: Vec<CreatureSummary>
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreatureSummary {
+    #[serde(default)]
+    id: u64,
+    name: String,
+    kind: CreatureKind,
+    turns_taken: usize,
+    // Damage is credited to whoever's turn it was when it was dealt
+    damage_dealt: usize,
+    damage_taken: usize,
+    effects_applied: Vec<String>,
+    effects_expired: Vec<String>,
+    defeated: bool
+}
+
+impl CombatSummary {
+    pub fn new() -> CombatSummary {
+        CombatSummary::default()
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    pub fn creatures(&self) -> &Vec<CreatureSummary> {
+        &self.creatures
+    }
+
+    pub fn defeated(&self) -> impl Iterator<Item = &CreatureSummary> {
+        self.creatures.iter().filter(|creature| creature.defeated)
+    }
+
+    pub fn has_kind(&self, kind: CreatureKind) -> bool {
+        self.creatures.iter().any(|creature| creature.kind == kind)
+    }
+
+    pub(crate) fn set_rounds(&mut self, rounds: usize) {
+        self.rounds = rounds;
+    }
+
+    // Finds the creature's record, adding one the first time it's seen. Renames and kinds tagged later are picked up
+    pub(crate) fn creature_mut(&mut self, id: u64, name: &str, kind: CreatureKind) -> &mut CreatureSummary {
+        let index = match self.creatures.iter().position(|creature| creature.id == id) {
+            Some(index) => index,
+            None => {
+                self.creatures.push(CreatureSummary::new(id, name.to_string(), kind));
+                self.creatures.len() - 1
+            }
+        };
+
+        let creature = &mut self.creatures[index];
+        creature.name = name.to_string();
+        creature.kind = kind;
+        creature
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Combat Summary\n\n");
+        markdown.push_str(&format!("Rounds: {}\n\n", self.rounds));
+
+        markdown.push_str("| Creature | Kind | Turns | Damage Dealt | Damage Taken | Effects Applied | Effects Expired | Defeated |\n");
+        markdown.push_str("| --- | --- | --- | --- | --- | --- | --- | --- |\n");
+        for creature in &self.creatures {
+            let defeated = if creature.defeated { "Yes" } else { "No" };
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {defeated} |\n",
+                creature.name, creature.kind, creature.turns_taken, creature.damage_dealt, creature.damage_taken,
+                creature.effects_applied.join(", "), creature.effects_expired.join(", ")
+            ));
+        }
+
+        let defeated: Vec<&str> = self.defeated().map(|creature| creature.name.as_str()).collect();
+        if !defeated.is_empty() {
+            markdown.push_str(&format!("\nDefeated: {}\n", defeated.join(", ")));
+        }
+
+        markdown
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl CreatureSummary {
+    fn new(id: u64, name: String, kind: CreatureKind) -> CreatureSummary {
+        CreatureSummary {
+            id, name, kind, turns_taken: 0, damage_dealt: 0, damage_taken: 0,
+            effects_applied: Vec::new(), effects_expired: Vec::new(), defeated: false
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> CreatureKind {
+        self.kind
+    }
+
+    pub fn turns_taken(&self) -> usize {
+        self.turns_taken
+    }
+
+    pub fn damage_dealt(&self) -> usize {
+        self.damage_dealt
+    }
+
+    pub fn damage_taken(&self) -> usize {
+        self.damage_taken
+    }
+
+    pub fn effects_applied(&self) -> &Vec<String> {
+        &self.effects_applied
+    }
+
+    pub fn effects_expired(&self) -> &Vec<String> {
+        &self.effects_expired
+    }
+
+    pub fn defeated(&self) -> bool {
+        self.defeated
+    }
+
+    pub(crate) fn add_turn(&mut self) {
+        self.turns_taken += 1;
+    }
+
+    pub(crate) fn add_damage_dealt(&mut self, amount: usize) {
+        self.damage_dealt += amount;
+    }
+
+    pub(crate) fn add_damage_taken(&mut self, amount: usize) {
+        self.damage_taken += amount;
+    }
+
+    pub(crate) fn add_effect_applied(&mut self, effect_name: String) {
+        self.effects_applied.push(effect_name);
+    }
+
+    pub(crate) fn add_effect_expired(&mut self, effect_name: String) {
+        self.effects_expired.push(effect_name);
+    }
+
+    pub(crate) fn set_defeated(&mut self, defeated: bool) {
+        self.defeated = defeated;
+    }
+}
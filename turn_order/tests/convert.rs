@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+use turn_order::turn_order::{
+    convert::Format,
+    creature::{kind::CreatureKind, status_effect::{TurnsLeft, ValueRule}, Creature},
+    TurnOrder
+};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+}
+
+fn creature<'a>(turn_order: &'a TurnOrder, name: &str) -> &'a Creature {
+    turn_order.creatures()
+        .find(|creature| creature.name() == name)
+        .unwrap_or_else(|| panic!("no creature named {name}"))
+}
+
+fn hit_points(creature: &Creature) -> Option<(usize, usize)> {
+    creature.hit_points().map(|hit_points| (hit_points.current(), hit_points.max()))
+}
+
+fn effect_names(creature: &Creature) -> Vec<&str> {
+    creature.status_effects().iter().map(|effect| effect.name()).collect()
+}
+
+// A turn order with a little of everything the formats can hold
+fn sample_turn_order() -> TurnOrder {
+    let mut turn_order = TurnOrder::new();
+    turn_order.add_creature(String::from("Aria"), 18);
+    turn_order.add_creature(String::from("Goblin"), 14);
+    turn_order.set_kind(0, CreatureKind::PlayerCharacter).unwrap();
+    turn_order.set_max_hit_points(1, 7).unwrap();
+    turn_order.damage(1, 2).unwrap();
+    turn_order.add_status_effect(1, String::from("Prone")).unwrap();
+    turn_order.add_status_effect_valued(1, String::from("Frightened"), 2, ValueRule::default()).unwrap();
+
+    turn_order
+}
+
+fn assert_sample_kept(turn_order: &TurnOrder) {
+    let names: Vec<&str> = turn_order.creatures().map(|creature| creature.name()).collect();
+    assert_eq!(names, ["Aria", "Goblin"]);
+
+    let goblin = creature(turn_order, "Goblin");
+    assert_eq!(goblin.initiative(), 14);
+    assert_eq!(hit_points(goblin), Some((5, 7)));
+    assert_eq!(effect_names(goblin), ["Prone", "Frightened"]);
+    assert_eq!(goblin.status_effects()[1].value(), Some(2));
+}
+
+#[test]
+fn csv_fixture_imports() {
+    let converted = TurnOrder::import(&fixture("sample.csv"), Format::Csv).unwrap();
+    let turn_order = converted.value();
+
+    let names: Vec<&str> = turn_order.creatures().map(|creature| creature.name()).collect();
+    assert_eq!(names, ["Aria", "Goblin 1", "Goblin 2", "Ogre, the Large"]);
+
+    assert_eq!(hit_points(creature(turn_order, "Aria")), Some((24, 30)));
+    assert_eq!(hit_points(creature(turn_order, "Goblin 2")), Some((7, 7)));
+    assert_eq!(hit_points(creature(turn_order, "Ogre, the Large")), None);
+
+    let goblin = creature(turn_order, "Goblin 1");
+    assert_eq!(effect_names(goblin), ["Prone", "Frightened"]);
+    assert_eq!(goblin.status_effects()[1].value(), Some(2));
+
+    assert_eq!(converted.unsupported(), &["Column \"ac\" is not supported by CSV import and was skipped."]);
+}
+
+#[test]
+fn improved_initiative_fixture_imports() {
+    let path = fixture("improved_initiative.json");
+    assert_eq!(Format::from_path(&path), Format::ImprovedInitiative);
+
+    let converted = TurnOrder::import(&path, Format::ImprovedInitiative).unwrap();
+    let turn_order = converted.value();
+
+    let names: Vec<&str> = turn_order.creatures().map(|creature| creature.name()).collect();
+    assert_eq!(names, ["Aria", "Goblin 1", "Goblin 2"]);
+    assert!(turn_order.combat_started());
+    assert_eq!(turn_order.round(), 2);
+    assert_eq!(turn_order.current_turn(), 1);
+
+    let aria = creature(turn_order, "Aria");
+    assert_eq!(aria.kind(), CreatureKind::PlayerCharacter);
+    assert_eq!(aria.initiative_modifier(), 3);
+    assert_eq!(hit_points(aria), Some((24, 30)));
+    assert!(matches!(aria.status_effects()[0].turns_left(), TurnsLeft::Finite(3)));
+
+    let goblin = creature(turn_order, "Goblin 1");
+    assert_eq!(effect_names(goblin), ["Prone", "Frightened"]);
+    assert_eq!(goblin.status_effects()[1].value(), Some(2));
+    assert_eq!(goblin.damage_modifiers().vulnerabilities(), &["radiant"]);
+    assert!(creature(turn_order, "Goblin 2").hidden());
+
+    let unsupported = converted.unsupported();
+    for expected in [
+        "Stat block field \"AC\" is not supported and was skipped.",
+        "Stat block field \"Actions\" is not supported and was skipped.",
+        "Temporary HP is not supported and was skipped.",
+        "Effect durations tied to another combatant's turn are counted on the affected creature's turn."
+    ] {
+        assert!(unsupported.iter().any(|field| field == expected), "missing \"{expected}\" in {unsupported:?}");
+    }
+    assert_eq!(unsupported.len(), 4, "unexpected report {unsupported:?}");
+}
+
+#[test]
+fn csv_round_trip() {
+    let exported = sample_turn_order().to_csv();
+    let imported = TurnOrder::from_csv(exported.value()).unwrap();
+
+    assert_sample_kept(imported.value());
+    assert!(imported.unsupported().is_empty());
+    // The player character's kind can't be written to CSV
+    assert_eq!(exported.unsupported(), &["Creature kinds are not supported by CSV: Aria."]);
+}
+
+#[test]
+fn improved_initiative_round_trip() {
+    let exported = sample_turn_order().to_improved_initiative().unwrap();
+    let imported = TurnOrder::from_improved_initiative(exported.value()).unwrap();
+
+    assert_sample_kept(imported.value());
+    assert_eq!(creature(imported.value(), "Aria").kind(), CreatureKind::PlayerCharacter);
+    assert!(exported.unsupported().is_empty(), "unexpected report {:?}", exported.unsupported());
+}
+
+#[test]
+fn improved_initiative_active_combatant_is_found_by_id() {
+    let json = r#"{
+        "RoundCounter": 1,
+        "ActiveCombatantId": "second",
+        "Combatants": [
+            { "Id": "first", "Initiative": 15, "StatBlock": { "Name": "Goblin" } },
+            { "Id": "second", "Initiative": 10, "StatBlock": { "Name": "Goblin" } }
+        ]
+    }"#;
+
+    let converted = TurnOrder::from_improved_initiative(json).unwrap();
+    let turn_order = converted.value();
+    assert_eq!(turn_order.entries()[0].initiative(), 15);
+    assert_eq!(turn_order.current_turn(), 1);
+}
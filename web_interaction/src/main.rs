@@ -0,0 +1,5 @@
+mod web_interaction;
+
+fn main() {
+    web_interaction::start();
+}
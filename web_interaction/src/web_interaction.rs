@@ -0,0 +1,560 @@
+use turn_order::autosave::Autosave;
+use turn_order::session::Session;
+use turn_order::storage;
+use turn_order::turn_order::{TurnOrder, creature::{Creature, status_effect::TurnsLeft}, entry::Target};
+use serde_json::{json, Value};
+use std::{env, fs, io::Write, path::PathBuf, sync::{mpsc, Arc, Mutex}, thread, time::{Duration, SystemTime}};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 8080;
+const TOKEN_VARIABLE: &str = "INITIATIVE_TOKEN";
+const PAGE: &str = include_str!("page.html");
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Config {
+    address: String,
+    port: u16,
+    token: String,
+    // The saved encounter to serve, the CLI or GUI autosave when not given
+    file: Option<PathBuf>
+}
+
+/*
+    The followed file owns the encounter. The server keeps the copy it
+    last read, and every change made through the API is written back
+    to the file straight away, so the DM's CLI or GUI picks it up from
+    there and its next autosave doesn't undo it.
+ */
+struct Encounter {
+    file: PathBuf,
+    followed: Mutex<Followed>,
+    // One sender per open event stream, closed streams are dropped on the next broadcast
+    subscribers: Mutex<Vec<mpsc::Sender<String>>>,
+    token: String
+}
+
+struct Followed {
+    // A plain turn order file is held as a session of one encounter
+    session: Session,
+    // So a plain turn order file is written back as one, not as a session
+    is_session: bool,
+    // When the file was last read or written, None until it has been
+    modified: Option<SystemTime>
+}
+
+impl Encounter {
+    fn following(file: PathBuf, token: String) -> Encounter {
+        Encounter {
+            file,
+            followed: Mutex::new(Followed { session: Session::new(), is_session: true, modified: None }),
+            subscribers: Mutex::new(Vec::new()),
+            token
+        }
+    }
+
+    // Reads the file again if it changed since it was last read or written, returns whether it did
+    fn reload(&self) -> Result<bool, String> {
+        let mut followed = self.followed.lock().unwrap();
+        let modified = fs::metadata(&self.file).and_then(|metadata| metadata.modified()).ok();
+        if modified.is_none() || modified == followed.modified {
+            return Ok(false);
+        }
+        followed.modified = modified;
+
+        match Session::load_from(&self.file) {
+            Ok(session) => {
+                followed.session = session;
+                followed.is_session = true;
+            }
+            Err(_) => {
+                let turn_order = TurnOrder::load_from(&self.file)?;
+                followed.session = Session::from_turn_order(String::from(Session::DEFAULT_NAME), turn_order);
+                followed.is_session = false;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /*
+        Makes a change to the active encounter and writes it back to the
+        file. If it can't be written the file is read again on the next
+        poll, so the server doesn't drift from what the DM has.
+     */
+    fn change<T>(&self, change: impl FnOnce(&mut TurnOrder) -> Result<T, ApiError>) -> Result<T, ApiError> {
+        let mut followed = self.followed.lock().unwrap();
+        let result = change(followed.session.current_mut())?;
+
+        let contents = match followed.is_session {
+            true => serde_json::to_string(&followed.session),
+            false => serde_json::to_string(followed.session.current())
+        };
+        let written = contents.map_err(|e| e.to_string())
+            .and_then(|contents| {
+                if let Some(parent) = self.file.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+                }
+                storage::write_atomic(&self.file, &contents)
+            });
+
+        match written {
+            Ok(_) => {
+                followed.modified = fs::metadata(&self.file).and_then(|metadata| metadata.modified()).ok();
+                Ok(result)
+            }
+            Err(e) => {
+                followed.modified = None;
+                Err(ApiError::from(e))
+            }
+        }
+    }
+
+    fn public_state(&self) -> Value {
+        public_state(self.followed.lock().unwrap().session.current())
+    }
+
+    /*
+        Sends an event to every open stream. The encounter lock
+        must not be held by the caller.
+     */
+    fn broadcast(&self, event: &str, updates: &[String]) {
+        let state = self.public_state();
+        let data = json!({ "updates": updates, "state": state });
+        let message = format!("event: {event}\ndata: {data}\n\n");
+
+        self.subscribers.lock().unwrap()
+            .retain(|subscriber| subscriber.send(message.clone()).is_ok());
+    }
+}
+
+// An error for the API to send back, with the status to send it with
+struct ApiError {
+    status: u16,
+    message: String
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> ApiError {
+        ApiError { status, message: message.into() }
+    }
+
+    fn response(self) -> Response<std::io::Cursor<Vec<u8>>> {
+        json_response(self.status, json!({ "error": self.message }))
+    }
+}
+
+// Anything that goes wrong writing the file back is the server's fault
+impl From<String> for ApiError {
+    fn from(message: String) -> ApiError {
+        ApiError::new(500, message)
+    }
+}
+
+/*
+    Read-only routes (the page, public state and event stream) are open
+    to anyone who can reach the server. Everything under /api/ needs
+    the shared token as "Authorization: Bearer <token>".
+ */
+fn handle_request(mut request: Request, encounter: Arc<Encounter>) {
+    let path = request.url().split('?').next().unwrap_or("").to_owned();
+    let method = request.method().clone();
+
+    let response = match (&method, path.as_str()) {
+        (Method::Get, "/") => Response::from_string(PAGE)
+            .with_header(content_type("text/html; charset=utf-8")),
+        (Method::Get, "/state") => json_response(200, encounter.public_state()),
+        (Method::Get, "/events") => {
+            stream_events(request, &encounter);
+            return;
+        }
+        (_, api_path) if api_path.starts_with("/api/") => {
+            if !authorized(&request, &encounter.token) {
+                json_response(401, json!({ "error": "Missing or invalid token." }))
+            }
+            else {
+                handle_api(&method, api_path, &mut request, &encounter)
+            }
+        }
+        _ => json_response(404, json!({ "error": "Not found." }))
+    };
+
+    if let Err(e) = request.respond(response) {
+        eprintln!("Error sending response: {e}");
+    }
+}
+
+fn handle_api(method: &Method, path: &str, request: &mut Request, encounter: &Encounter) -> Response<std::io::Cursor<Vec<u8>>> {
+    match (method, path) {
+        (Method::Get, "/api/state") => {
+            let followed = encounter.followed.lock().unwrap();
+            match serde_json::to_value(followed.session.current()) {
+                Ok(state) => json_response(200, state),
+                Err(e) => json_response(500, json!({ "error": e.to_string() }))
+            }
+        }
+        (Method::Post, "/api/next-turn") => {
+            match encounter.change(|turn_order| turn_order.next_turn().map_err(|e| ApiError::new(409, e))) {
+                Ok(updates) => {
+                    encounter.broadcast("turn", &updates);
+                    json_response(200, json!({ "updates": updates }))
+                }
+                Err(e) => e.response()
+            }
+        }
+        (Method::Post, "/api/creature") => {
+            let (name, initiative, hidden) = match read_new_creature(request) {
+                Ok(creature) => creature,
+                Err(e) => return json_response(400, json!({ "error": e }))
+            };
+
+            let result = encounter.change(|turn_order| {
+                match hidden {
+                    true => turn_order.add_creature_hidden(name, initiative),
+                    false => turn_order.add_creature(name, initiative)
+                }
+                Ok(())
+            });
+            match result {
+                Ok(_) => {
+                    encounter.broadcast("state", &[]);
+                    json_response(201, json!({}))
+                }
+                Err(e) => e.response()
+            }
+        }
+        /*
+            Expects a body like {"creature": "Goblin 2"}. Creatures are found
+            by name since hidden ones aren't in the public state, so its
+            positions don't line up with the turn order's.
+         */
+        (Method::Post, "/api/reveal") => {
+            let name = match read_json_body(request) {
+                Ok(value) => value["creature"].as_str().map(str::to_owned),
+                Err(e) => return json_response(400, json!({ "error": e }))
+            };
+            let Some(name) = name else {
+                return json_response(400, json!({ "error": "Missing creature name." }));
+            };
+
+            let result = encounter.change(|turn_order| {
+                match find_target(turn_order, &name) {
+                    Some(target) => turn_order.reveal_creature(target).map_err(|e| ApiError::new(400, e)),
+                    None => Err(ApiError::new(404, format!("No creature named {name}.")))
+                }
+            });
+            match result {
+                Ok(update) => {
+                    let updates = vec![update];
+                    encounter.broadcast("reveal", &updates);
+                    json_response(200, json!({ "updates": updates }))
+                }
+                Err(e) => e.response()
+            }
+        }
+        _ => json_response(404, json!({ "error": "Not found." }))
+    }
+}
+
+// Entries are matched before group members, ignoring case
+fn find_target(turn_order: &TurnOrder, name: &str) -> Option<Target> {
+    let entries = turn_order.entries();
+    if let Some(index) = entries.iter().position(|entry| entry.name().eq_ignore_ascii_case(name)) {
+        return Some(Target::entry(index));
+    }
+
+    entries.iter().enumerate()
+        .filter_map(|(index, entry)| Some((index, entry.as_group()?)))
+        .find_map(|(index, group)| {
+            let member = group.members().iter().position(|member| member.name().eq_ignore_ascii_case(name))?;
+            Some(Target::member(index, member))
+        })
+}
+
+// Expects a body like {"name": "Goblin", "initiative": 12}, with an optional "hidden": true
+fn read_new_creature(request: &mut Request) -> Result<(String, isize, bool), String> {
+    let value = read_json_body(request)?;
+    let name = value["name"].as_str().ok_or("Missing creature name.")?;
+    let initiative = value["initiative"].as_i64().ok_or("Missing creature initiative.")?;
+
+    let hidden = value["hidden"].as_bool().unwrap_or(false);
+
+    Ok((name.to_owned(), initiative as isize, hidden))
+}
+
+fn read_json_body(request: &mut Request) -> Result<Value, String> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).map_err(|e| e.to_string())?;
+
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+/*
+    Server-sent events. tiny_http buffers chunked responses, so the
+    headers and events are written to the raw connection instead.
+ */
+fn stream_events(request: Request, encounter: &Encounter) {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let state = encounter.public_state();
+    encounter.subscribers.lock().unwrap().push(sender);
+
+    let mut writer = request.into_writer();
+    let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    let initial = format!("event: state\ndata: {}\n\n", json!({ "updates": [], "state": state }));
+
+    if writer.write_all(headers.as_bytes()).and_then(|_| writer.write_all(initial.as_bytes())).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+
+    for message in receiver {
+        if writer.write_all(message.as_bytes()).and_then(|_| writer.flush()).is_err() {
+            break;
+        }
+    }
+}
+
+// What players are allowed to see: no hidden creatures or effects
+fn public_state(turn_order: &TurnOrder) -> Value {
+    let creatures: Vec<Value> = turn_order.entries().iter().enumerate()
+        .filter(|(_, entry)| !entry.hidden())
+        .map(|(index, entry)| {
+            let effects = entry.as_creature().map(public_effects).unwrap_or_default();
+            // Group members are listed when the DM has the group expanded
+            let members: Vec<Value> = entry.as_group()
+                .filter(|group| group.expanded())
+                .map(|group| group.members().iter()
+                    .filter(|member| !member.hidden())
+                    .map(|member| json!({ "name": member.name(), "effects": public_effects(member) }))
+                    .collect())
+                .unwrap_or_default();
+
+            json!({
+                "name": entry.name(),
+                "initiative": turn_order.initiative_system().initiative_display(entry),
+                "kind": entry.kind().map(|kind| kind.to_string()),
+                "current": index == turn_order.current_turn(),
+                "effects": effects,
+                "members": members
+            })
+        })
+        .collect();
+
+    // "Round 2", or "Round 2, Pass 3" under multi-pass initiative, once combat starts
+    let round = turn_order.combat_started().then(|| turn_order.round_display());
+    json!({ "round": round, "creatures": creatures })
+}
+
+fn public_effects(creature: &Creature) -> Vec<Value> {
+    creature.status_effects().iter()
+        .filter(|effect| !effect.hidden())
+        .map(|effect| {
+            let turns_left = match effect.turns_left() {
+                TurnsLeft::Indefinite => Value::Null,
+                TurnsLeft::Finite(turns) => json!(turns)
+            };
+            json!({ "name": effect.name(), "turns_left": turns_left })
+        })
+        .collect()
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {token}");
+    request.headers().iter()
+        .any(|header| header.field.equiv("Authorization") && header.value.as_str() == expected)
+}
+
+fn json_response(status: u16, value: Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(content_type("application/json"))
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).expect("Content type header should be valid")
+}
+
+/*
+    Reloads the encounter whenever the file changes, so the page keeps
+    up with the DM's CLI or GUI as it autosaves.
+ */
+fn follow_file(encounter: Arc<Encounter>) {
+    loop {
+        match encounter.reload() {
+            Ok(true) => encounter.broadcast("state", &[]),
+            Ok(false) => (),
+            Err(e) => eprintln!("Error loading {}: {e}", encounter.file.display())
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/*
+    Reads --address, --port, --token and --file from the command line.
+    The token may also come from the INITIATIVE_TOKEN environment
+    variable.
+ */
+fn read_config() -> Result<Config, String> {
+    let mut config = Config {
+        address: String::from(DEFAULT_ADDRESS),
+        port: DEFAULT_PORT,
+        token: env::var(TOKEN_VARIABLE).unwrap_or_default(),
+        file: None
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("Missing value for {arg}"))?;
+        match arg.as_str() {
+            "--address" => config.address = value,
+            "--port" => config.port = value.parse().map_err(|e| format!("Invalid port: {e}"))?,
+            "--token" => config.token = value,
+            "--file" => config.file = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unrecognized argument {arg}"))
+        }
+    }
+
+    if config.token.is_empty() {
+        return Err(format!("A token is required, pass --token or set {TOKEN_VARIABLE}."));
+    }
+
+    Ok(config)
+}
+
+pub fn start() {
+    let config = match read_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("Usage: web_interaction --token <token> [--address 127.0.0.1] [--port 8080] [--file <turn order or session file>]");
+            return;
+        }
+    };
+
+    let file = match config.file {
+        Some(file) if !file.exists() => {
+            eprintln!("{} does not exist.", file.display());
+            return;
+        }
+        Some(file) => file,
+        None => match Autosave::new() {
+            Ok(autosave) => autosave.path().to_path_buf(),
+            Err(e) => {
+                eprintln!("Error finding the autosave, pass --file instead: {e}");
+                return;
+            }
+        }
+    };
+
+    let address = format!("{}:{}", config.address, config.port);
+    let server = match Server::http(&address) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error starting server on {address}: {e}");
+            return;
+        }
+    };
+    println!("Serving the encounter on http://{address}");
+    println!("Following {}", file.display());
+
+    let encounter = Arc::new(Encounter::following(file, config.token));
+    let following = Arc::clone(&encounter);
+    thread::spawn(move || follow_file(following));
+
+    for request in server.incoming_requests() {
+        let encounter = Arc::clone(&encounter);
+        thread::spawn(move || handle_request(request, encounter));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Read, net::TcpStream};
+    use turn_order::turn_order::initiative::{cards::CardInitiative, MultiPass};
+
+    fn temp_file(test: &str) -> PathBuf {
+        env::temp_dir().join(format!("web_interaction_{}_{test}.json", std::process::id()))
+    }
+
+    // Sends one request through a real server and returns the raw response
+    fn send(encounter: &Arc<Encounter>, request: &str) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        let serving = Arc::clone(encounter);
+        let handler = thread::spawn(move || handle_request(server.recv().unwrap(), serving));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        handler.join().unwrap();
+
+        response
+    }
+
+    #[test]
+    fn next_turn_from_the_api_outlasts_the_dms_autosave() {
+        let path = temp_file("next_turn");
+        let mut session = Session::new();
+        session.current_mut().add_creature(String::from("Aria"), 18);
+        session.current_mut().add_creature(String::from("Brom"), 12);
+        session.current_mut().start_combat().unwrap();
+        let mut autosave = Autosave::at(path.clone());
+        autosave.save(&session).unwrap();
+
+        let encounter = Arc::new(Encounter::following(path.clone(), String::from("secret")));
+        assert!(encounter.reload().unwrap());
+
+        // File timestamps are coarse, so each write is spaced out to be seen as a change
+        thread::sleep(Duration::from_millis(50));
+        let response = send(&encounter, "POST /api/next-turn HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer secret\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        // The DM's tracker picks the change up and carries on from it
+        let mut session = autosave.changed_elsewhere().unwrap().expect("the server should have written the file");
+        assert_eq!(session.current().current_turn(), 1);
+        session.current_mut().add_creature(String::from("Cade"), 5);
+        thread::sleep(Duration::from_millis(50));
+        autosave.save(&session).unwrap();
+
+        assert!(encounter.reload().unwrap());
+        let state = encounter.public_state();
+        let current: Vec<&str> = state["creatures"].as_array().unwrap().iter()
+            .filter(|creature| creature["current"] == true)
+            .map(|creature| creature["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(current, ["Brom"]);
+        assert_eq!(state["creatures"].as_array().unwrap().len(), 3);
+
+        autosave.clear().unwrap();
+    }
+
+    #[test]
+    fn changes_need_the_token() {
+        let path = temp_file("token");
+        let encounter = Arc::new(Encounter::following(path.clone(), String::from("secret")));
+
+        let response = send(&encounter, "POST /api/next-turn HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn players_see_dealt_cards_and_the_round() {
+        let mut turn_order = TurnOrder::new();
+        turn_order.add_creature(String::from("Aria"), 3);
+        turn_order.set_initiative_system(Box::new(CardInitiative::with_seed(7)));
+        turn_order.start_combat().unwrap();
+
+        let state = public_state(&turn_order);
+        let initiative = turn_order.initiative_system().initiative_display(&turn_order.entries()[0]);
+        assert_eq!(state["creatures"][0]["initiative"], initiative);
+        assert_ne!(initiative, format!("I:{}", turn_order.entries()[0].initiative()));
+        assert_eq!(state["round"], "Round 1");
+
+        turn_order.set_initiative_system(Box::new(MultiPass));
+        turn_order.start_combat().unwrap();
+        assert_eq!(public_state(&turn_order)["round"], "Round 1, Pass 1");
+    }
+}